@@ -1,6 +1,6 @@
 use lazy_static::lazy_static;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CharacterSet {
     pub right_arrow: char,
     pub horizontal_bar: char,
@@ -10,6 +10,7 @@ pub struct CharacterSet {
     pub left_cross: char,
     pub vertical_bar_breaking: char,
     pub vertical_ellipsis: char,
+    pub horizontal_ellipsis: char,
     pub left_top: char,
     pub left_bottom: char,
     pub right_bottom: char,
@@ -17,6 +18,7 @@ pub struct CharacterSet {
 }
 
 impl CharacterSet {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         right_arrow: char,
         horizontal_bar: char,
@@ -26,6 +28,7 @@ impl CharacterSet {
         left_cross: char,
         vertical_bar_breaking: char,
         vertical_ellipsis: char,
+        horizontal_ellipsis: char,
         left_top: char,
         left_bottom: char,
         right_bottom: char,
@@ -40,6 +43,7 @@ impl CharacterSet {
             left_cross,
             vertical_bar_breaking,
             vertical_ellipsis,
+            horizontal_ellipsis,
             left_top,
             left_bottom,
             right_bottom,
@@ -49,8 +53,12 @@ impl CharacterSet {
 }
 
 lazy_static! {
-    pub static ref UNICODE: CharacterSet =
-        CharacterSet::new('▶', '─', '│', '┼', '┬', '├', '·', '⋮', '╭', '╰', '╯', '─');
+    pub static ref UNICODE: CharacterSet = CharacterSet::new(
+        '▶', '─', '│', '┼', '┬', '├', '·', '⋮', '…', '╭', '╰', '╯', '─'
+    );
+    pub static ref HEAVY: CharacterSet = CharacterSet::new(
+        '▶', '━', '┃', '╋', '┳', '┣', '╏', '⋮', '…', '┏', '┗', '┛', '━'
+    );
     pub static ref ASCII: CharacterSet =
-        CharacterSet::new('>', '-', '|', '+', '|', '|', '*', ':', ',', '`', '\'', '^');
+        CharacterSet::new('>', '-', '|', '+', '|', '|', '*', ':', '~', ',', '`', '\'', '^');
 }
@@ -0,0 +1,69 @@
+use super::report::ReportType;
+use phf::phf_map;
+
+/// A registered diagnostic code with its long-form explanation, which is
+/// what `yakou explain <code>` prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub report_type: ReportType,
+    pub summary: &'static str,
+    pub explanation: &'static str,
+}
+
+/// Declares every code as a constant and registers it in the catalog, so
+/// referring to a code that isn't registered fails to compile. Explanations
+/// live in `codes/<code>.md`.
+macro_rules! error_codes {
+    ($($code:ident = $name:literal: $report_type:ident, $summary:literal;)*) => {
+        $(
+            const _: () = assert!(
+                same_name($name, stringify!($code)),
+                concat!("code name ", $name, " does not match its constant")
+            );
+
+            pub const $code: ErrorCode = ErrorCode {
+                code: $name,
+                report_type: ReportType::$report_type,
+                summary: $summary,
+                explanation: include_str!(concat!("codes/", $name, ".md")),
+            };
+        )*
+
+        static CODES: phf::Map<&'static str, ErrorCode> = phf_map! {
+            $($name => $code,)*
+        };
+    };
+}
+
+error_codes! {
+    E01 = "E01": Error, "unexpected token";
+    E02 = "E02": Error, "duplicate class definition";
+    E03 = "E03": Error, "implementation of an unknown class";
+    W01 = "W01": Warning, "empty implementation block";
+}
+
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    CODES.get(code)
+}
+
+pub fn all() -> impl Iterator<Item = &'static ErrorCode> {
+    CODES.values()
+}
+
+const fn same_name(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut index = 0;
+    while index < a.len() {
+        if a[index] != b[index] {
+            return false;
+        }
+        index += 1;
+    }
+
+    true
+}
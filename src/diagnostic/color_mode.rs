@@ -0,0 +1,29 @@
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    Always,
+    Never,
+    /// Colors only terminals, unless `NO_COLOR` or `CLICOLOR_FORCE` say
+    /// otherwise.
+    #[default]
+    Auto,
+}
+
+impl ColorMode {
+    pub fn is_enabled(&self, is_terminal: bool) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+                    false
+                } else if env::var_os("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+                    true
+                } else {
+                    is_terminal
+                }
+            }
+        }
+    }
+}
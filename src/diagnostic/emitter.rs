@@ -0,0 +1,70 @@
+use super::{
+    github::GithubEmitter, html::HtmlEmitter, human_emitter::HumanEmitter, json::JsonEmitter,
+    report::Report, sarif::SarifEmitter, short::ShortEmitter, source_map::SourceMap, theme::Theme,
+};
+use std::{
+    fmt::Debug,
+    io::{self, Write},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// A diagnostic backend. Emitters receive reports whose spans are already
+/// resolved against `source_map`, and whether colors are enabled for the
+/// stream they write to.
+pub trait Emitter: Debug + Send + Sync {
+    fn emit(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        reports: &[Report],
+        colored: bool,
+    ) -> io::Result<()>;
+
+    /// The theme the emitter draws with, which also picks label colors and
+    /// styles the summary of a [`DiagnosticSink`](super::sink::DiagnosticSink).
+    fn active_theme(&self) -> Option<&Theme> {
+        None
+    }
+}
+
+/// The built-in emitters, as selected by `--error-format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    #[default]
+    Human,
+    Short,
+    Json,
+    Sarif,
+    Html,
+    Github,
+}
+
+impl ErrorFormat {
+    pub fn emitter(&self, human_emitter: HumanEmitter) -> Arc<dyn Emitter> {
+        match self {
+            ErrorFormat::Human => Arc::new(human_emitter),
+            ErrorFormat::Short => Arc::new(ShortEmitter::new(human_emitter.theme.clone())),
+            ErrorFormat::Json => Arc::new(JsonEmitter::new(Some(human_emitter))),
+            ErrorFormat::Sarif => Arc::new(SarifEmitter),
+            ErrorFormat::Html => Arc::new(HtmlEmitter::new(human_emitter, true)),
+            ErrorFormat::Github => Arc::new(GithubEmitter),
+        }
+    }
+}
+
+impl FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "human" => Ok(ErrorFormat::Human),
+            "short" => Ok(ErrorFormat::Short),
+            "json" => Ok(ErrorFormat::Json),
+            "sarif" => Ok(ErrorFormat::Sarif),
+            "html" => Ok(ErrorFormat::Html),
+            "github" => Ok(ErrorFormat::Github),
+            _ => Err(format!("unknown error format `{}`", format)),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use super::{
+    emitter::Emitter,
+    report::{Report, ReportType},
+    source_map::{file_name, SourceMap},
+};
+use std::io::{self, Write};
+
+/// Writes each report as a GitHub Actions workflow command such as
+/// `::error file=src/main.yk,line=1,col=1,endLine=1,endColumn=6,title=E01::message`,
+/// which Actions turns into an annotation on the pull request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GithubEmitter;
+
+impl GithubEmitter {
+    /// Renders the commands for a report and its children, one per line.
+    pub fn render_report(&self, source_map: &SourceMap, report: &Report) -> String {
+        let span = &report.common_span;
+        let start_line = span.start_position.line.max(1);
+        let start_column = span.start_position.column + 1;
+        let end_line = span.end_position.line.max(start_line);
+        // The exclusive 0-based end column is the inclusive 1-based one, which
+        // only has to stay after the start column on a single line.
+        let end_column = match end_line == start_line {
+            true => span.end_position.column.max(start_column),
+            false => span.end_position.column.max(1),
+        };
+        let message = report
+            .footers
+            .iter()
+            .fold(report.message.clone(), |message, footer| {
+                format!(
+                    "{}\n{}: {}",
+                    message,
+                    footer.report_type.name(),
+                    footer.message
+                )
+            });
+
+        let mut command = format!(
+            "::{} file={},line={},col={},endLine={},endColumn={},title={}::{}\n",
+            command_name(report.report_type),
+            escape_property(&file_name(source_map, report.file_id).replace('\\', "/")),
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+            escape_property(&report.tag),
+            escape_data(&message)
+        );
+        for child in &report.children {
+            command.push_str(&self.render_report(source_map, child));
+        }
+
+        command
+    }
+}
+
+impl Emitter for GithubEmitter {
+    fn emit(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        reports: &[Report],
+        _colored: bool,
+    ) -> io::Result<()> {
+        for report in reports {
+            write!(print_stream, "{}", self.render_report(source_map, report))?;
+        }
+
+        print_stream.flush()
+    }
+}
+
+/// Returns the workflow command for a severity. Actions only knows errors,
+/// warnings and notices.
+pub fn command_name(report_type: ReportType) -> &'static str {
+    match report_type {
+        ReportType::Error | ReportType::Bug => "error",
+        ReportType::Warning => "warning",
+        ReportType::Note | ReportType::Help | ReportType::Info => "notice",
+    }
+}
+
+/// Escapes the message of a workflow command.
+pub fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a `key=value` property of a workflow command, where `:` and `,`
+/// would otherwise end the value.
+pub fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
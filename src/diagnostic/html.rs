@@ -0,0 +1,181 @@
+use super::{
+    emitter::Emitter,
+    human_emitter::HumanEmitter,
+    report::Report,
+    source_map::SourceMap,
+    theme::{color_name, Theme},
+};
+use anstyle::{Ansi256Color, Color, Effects, RgbColor, Style};
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
+/// Styles for every class the renderer can produce, on a dark background like
+/// most terminals.
+pub const STYLESHEET: &str = "\
+.yk-report { background: #1e1e1e; color: #d4d4d4; padding: 0.75em 1em; \
+font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; line-height: 1.3; }
+.yk-black { color: #000000; }
+.yk-red { color: #cd3131; }
+.yk-green { color: #0dbc79; }
+.yk-yellow { color: #e5e510; }
+.yk-blue { color: #2472c8; }
+.yk-magenta { color: #bc3fbc; }
+.yk-cyan { color: #11a8cd; }
+.yk-white { color: #e5e5e5; }
+.yk-bright-black { color: #767676; }
+.yk-bright-red { color: #f14c4c; }
+.yk-bright-green { color: #23d18b; }
+.yk-bright-yellow { color: #f5f543; }
+.yk-bright-blue { color: #3b8eea; }
+.yk-bright-magenta { color: #d670d6; }
+.yk-bright-cyan { color: #29b8db; }
+.yk-bright-white { color: #ffffff; }
+.yk-bold { font-weight: bold; }
+.yk-dimmed { opacity: 0.7; }
+.yk-italic { font-style: italic; }
+.yk-underline { text-decoration: underline; }
+.yk-strikethrough { text-decoration: line-through; }
+";
+
+/// Renders reports with the same layout as [`HumanEmitter`], one
+/// `<pre class="yk-report">` per report. As a full page, the reports are
+/// wrapped in a standalone HTML document embedding [`STYLESHEET`].
+#[derive(Debug, Clone, Default)]
+pub struct HtmlEmitter {
+    pub human_emitter: HumanEmitter,
+    pub full_page: bool,
+}
+
+impl HtmlEmitter {
+    pub fn new(human_emitter: HumanEmitter, full_page: bool) -> Self {
+        Self {
+            human_emitter,
+            full_page,
+        }
+    }
+}
+
+impl Emitter for HtmlEmitter {
+    fn emit(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        reports: &[Report],
+        _colored: bool,
+    ) -> io::Result<()> {
+        if self.full_page {
+            write!(
+                print_stream,
+                "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+                 <title>Diagnostics</title>\n<style>\n{}</style>\n</head>\n<body>\n",
+                STYLESHEET
+            )?;
+        }
+
+        for report in reports {
+            write!(print_stream, "<pre class=\"yk-report\">")?;
+            self.human_emitter
+                .render_html(print_stream, source_map, report)?;
+            writeln!(print_stream, "</pre>")?;
+        }
+
+        if self.full_page {
+            writeln!(print_stream, "</body>\n</html>")?;
+        }
+
+        Ok(())
+    }
+
+    fn active_theme(&self) -> Option<&Theme> {
+        Some(&self.human_emitter.theme)
+    }
+}
+
+pub fn escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Opens a `<span>` carrying the classes for `style`. RGB colors have no
+/// class and are written as an inline style instead.
+pub fn open_span(style: &Style) -> String {
+    let mut classes = Vec::new();
+    let mut inline_style = None;
+
+    match style.get_fg_color().map(|color| match color {
+        Color::Ansi256(color) => color.into_ansi().map_or(color_to_rgb(color), Color::Ansi),
+        color => color,
+    }) {
+        Some(Color::Rgb(color)) => {
+            inline_style = Some(format!(
+                "color: #{:02x}{:02x}{:02x}",
+                color.r(),
+                color.g(),
+                color.b()
+            ))
+        }
+        Some(color) => classes.push(format!("yk-{}", color_name(color).replace('_', "-"))),
+        None => {}
+    }
+
+    let effects = style.get_effects();
+    for (effect, class) in [
+        (Effects::BOLD, "yk-bold"),
+        (Effects::DIMMED, "yk-dimmed"),
+        (Effects::ITALIC, "yk-italic"),
+        (Effects::UNDERLINE, "yk-underline"),
+        (Effects::STRIKETHROUGH, "yk-strikethrough"),
+    ] {
+        if effects.contains(effect) {
+            classes.push(class.to_string());
+        }
+    }
+
+    match inline_style {
+        Some(inline_style) => format!(
+            "<span class=\"{}\" style=\"{}\">",
+            classes.join(" "),
+            inline_style
+        ),
+        None => format!("<span class=\"{}\">", classes.join(" ")),
+    }
+}
+
+/// Converts an extended 256-color index, one not among the 16 ANSI colors,
+/// to the RGB value xterm draws it with: a 6x6x6 cube followed by a ramp
+/// of grays.
+fn color_to_rgb(color: Ansi256Color) -> Color {
+    let index = color.index();
+    let rgb = match index {
+        16..=231 => {
+            let level = |value: u8| match value {
+                0 => 0,
+                value => 55 + value * 40,
+            };
+            let index = index - 16;
+            RgbColor(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + index.saturating_sub(232) * 10;
+            RgbColor(gray, gray, gray)
+        }
+    };
+
+    Color::Rgb(rgb)
+}
@@ -0,0 +1,1223 @@
+use super::{
+    char_set::CharacterSet,
+    emitter::Emitter,
+    html,
+    label::Label,
+    line::{char_width, Line},
+    position::Position,
+    report::{Footer, Report},
+    source::Source,
+    source_map::{FileId, SourceMap},
+    span::Span,
+    suggestion::Suggestion,
+    theme::Theme,
+};
+use anstyle::{Color, Reset, Style};
+use ilog::IntLog;
+use std::{
+    borrow::Cow,
+    cmp::{max, Reverse},
+    io::{self, Write},
+    ops::RangeInclusive,
+};
+
+/// Gaps between shown lines of at most this many lines are shown rather
+/// than folded into a `lines omitted` row.
+const MAX_UNFOLDED_GAP: usize = 3;
+
+/// The boxed terminal renderer: source snippets with a line-number gutter,
+/// underlined labels and rails for multi-line labels.
+#[derive(Debug, Clone, Default)]
+pub struct HumanEmitter {
+    pub theme: Theme,
+    pub tab_width: usize,
+    /// Lines of source shown before each line a label starts or ends on.
+    pub context_before: usize,
+    /// Lines of source shown after each line a label starts or ends on.
+    pub context_after: usize,
+    /// Snippets spanning at most this many lines are shown in full instead of
+    /// being folded.
+    pub full_span_lines: usize,
+    /// Source lines wider than this many cells are cut down to a window
+    /// around their labels.
+    pub max_width: Option<usize>,
+}
+
+impl HumanEmitter {
+    pub fn new(character_set: CharacterSet, tab_width: usize) -> Self {
+        Self {
+            theme: Theme {
+                character_set,
+                ..Theme::default()
+            },
+            tab_width,
+            context_before: 1,
+            context_after: 1,
+            full_span_lines: 0,
+            max_width: None,
+        }
+    }
+
+    pub fn context_lines(&mut self, before: usize, after: usize) -> Self {
+        self.context_before = before;
+        self.context_after = after;
+        self.clone()
+    }
+
+    pub fn full_span_lines(&mut self, full_span_lines: usize) -> Self {
+        self.full_span_lines = full_span_lines;
+        self.clone()
+    }
+
+    pub fn max_width(&mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self.clone()
+    }
+
+    pub fn theme(&mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self.clone()
+    }
+
+    /// Renders a single report, as the `rendered` field of machine-readable
+    /// output does.
+    pub fn render_report(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        report: &Report,
+        colored: bool,
+    ) -> io::Result<()> {
+        Renderer::new(self, source_map, Markup::new(colored)).render_report(print_stream, report)
+    }
+
+    /// Renders a single report with CSS classes in place of colors and all
+    /// source text escaped, for embedding in a `<pre>` element.
+    pub fn render_html(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        report: &Report,
+    ) -> io::Result<()> {
+        Renderer::new(self, source_map, Markup::Html).render_report(print_stream, report)
+    }
+}
+
+impl Emitter for HumanEmitter {
+    fn emit(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        reports: &[Report],
+        colored: bool,
+    ) -> io::Result<()> {
+        let renderer = Renderer::new(self, source_map, Markup::new(colored));
+
+        for report in reports {
+            renderer.render_report(print_stream, report)?;
+        }
+
+        Ok(())
+    }
+
+    fn active_theme(&self) -> Option<&Theme> {
+        Some(&self.theme)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Markup {
+    Plain,
+    Ansi,
+    Html,
+}
+
+impl Markup {
+    fn new(colored: bool) -> Self {
+        if colored {
+            Markup::Ansi
+        } else {
+            Markup::Plain
+        }
+    }
+}
+
+struct Renderer<'a> {
+    source_map: &'a SourceMap,
+    theme: &'a Theme,
+    tab_width: usize,
+    context_before: usize,
+    context_after: usize,
+    full_span_lines: usize,
+    max_width: Option<usize>,
+    markup: Markup,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(emitter: &'a HumanEmitter, source_map: &'a SourceMap, markup: Markup) -> Self {
+        Self {
+            source_map,
+            theme: &emitter.theme,
+            tab_width: emitter.tab_width,
+            context_before: emitter.context_before,
+            context_after: emitter.context_after,
+            full_span_lines: emitter.full_span_lines,
+            max_width: emitter.max_width,
+            markup,
+        }
+    }
+
+    fn render_report(&self, print_stream: &mut dyn Write, report: &Report) -> io::Result<()> {
+        let max_number_of_digit = report
+            .flatten()
+            .into_iter()
+            .flat_map(|report| {
+                report
+                    .labels
+                    .iter()
+                    .flat_map(|label| {
+                        label
+                            .suggestions
+                            .iter()
+                            .map(|suggestion| suggestion.span.end_position.line)
+                            .chain([label.span.end_position.line + self.context_after])
+                    })
+                    .chain([
+                        report.common_span.start_position.line,
+                        report.common_span.end_position.line + self.context_after,
+                    ])
+            })
+            .map(|line_number| usize::log10(line_number.max(1)) + 1)
+            .max()
+            .unwrap_or(1);
+
+        self.write_style(print_stream, self.theme.header_style(report.report_type))?;
+
+        writeln!(
+            print_stream,
+            "[{}] {}{}",
+            self.text(&report.tag),
+            self.reset(),
+            self.text(&report.message)
+        )?;
+
+        self.render_body(print_stream, report, false, max_number_of_digit)?;
+
+        self.write_style(print_stream, self.theme.gutter)?;
+        writeln!(
+            print_stream,
+            "{}{}{}",
+            self.theme
+                .character_set
+                .horizontal_bar
+                .to_string()
+                .repeat(max_number_of_digit + 1),
+            self.theme.character_set.right_bottom,
+            self.reset()
+        )?;
+        print_stream.flush()
+    }
+
+    /// Renders the snippets and footers of a report, then each of its
+    /// children under a header of their own.
+    fn render_body(
+        &self,
+        print_stream: &mut dyn Write,
+        report: &Report,
+        is_child: bool,
+        max_number_of_digit: usize,
+    ) -> io::Result<()> {
+        for (index, file_id) in report.files().into_iter().enumerate() {
+            let mut section = report.clone();
+            section
+                .labels
+                .retain(|label| label.file_id == Some(file_id));
+
+            if index > 0 && section.labels.is_empty() {
+                continue;
+            }
+
+            self.render_snippet(
+                print_stream,
+                &section,
+                file_id,
+                index == 0 && !is_child,
+                (index == 0).then_some(report.common_span),
+                max_number_of_digit,
+            )?;
+        }
+
+        for footer in &report.footers {
+            self.write_footer(print_stream, footer, max_number_of_digit)?;
+        }
+
+        for child in &report.children {
+            self.write_style(print_stream, self.theme.gutter)?;
+            write!(
+                print_stream,
+                "{:>width$}{} {}",
+                self.theme.character_set.left_cross,
+                self.theme.character_set.horizontal_bar,
+                self.reset(),
+                width = max_number_of_digit + 2
+            )?;
+            self.write_style(print_stream, self.theme.header_style(child.report_type))?;
+            writeln!(
+                print_stream,
+                "[{}] {}{}",
+                self.text(&child.tag),
+                self.reset(),
+                self.text(&child.message)
+            )?;
+
+            self.render_body(print_stream, child, true, max_number_of_digit)?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the labels of `report` in one file. `own_span` is the report's
+    /// own span when the file is the report's own. Spans the source can't
+    /// show are listed below the snippet instead of being drawn.
+    fn render_snippet(
+        &self,
+        print_stream: &mut dyn Write,
+        report: &Report,
+        file_id: FileId,
+        is_first_section: bool,
+        own_span: Option<Span>,
+        max_number_of_digit: usize,
+    ) -> io::Result<()> {
+        let location = match (own_span, report.labels.first()) {
+            (Some(span), _) => span.start_position,
+            (None, Some(label)) => label.span.start_position,
+            (None, None) => return Ok(()),
+        };
+        self.write_source_location(
+            print_stream,
+            max_number_of_digit,
+            file_id,
+            is_first_section,
+            &location,
+        )?;
+
+        let source = match self.source_map.get(file_id) {
+            Some(source) => source,
+            None => {
+                self.write_line_number(print_stream, usize::MAX, max_number_of_digit, true)?;
+                self.write_style(print_stream, self.theme.gutter)?;
+                return match self.source_map.load_error(file_id) {
+                    Some(error) => writeln!(
+                        print_stream,
+                        "source not available: {}{}",
+                        self.text(&error),
+                        self.reset()
+                    ),
+                    None => writeln!(print_stream, "source not available{}", self.reset()),
+                };
+            }
+        };
+        let (labels, invalid_labels): (Vec<Label>, Vec<Label>) = report
+            .labels
+            .iter()
+            .cloned()
+            .partition(|label| span_error(&label.span, &source).is_none());
+        let own_span_error = own_span.and_then(|span| span_error(&span, &source));
+        let common_span = own_span
+            .filter(|_| own_span_error.is_none())
+            .into_iter()
+            .chain(labels.iter().map(|label| label.span))
+            .reduce(|span, label_span| span.union(&label_span));
+        let report = &Report {
+            labels,
+            common_span: own_span
+                .filter(|_| own_span_error.is_none())
+                .or(common_span)
+                .unwrap_or(report.common_span),
+            ..report.clone()
+        };
+
+        let segment = match common_span {
+            Some(span) => source.sub_list(
+                span.start_position
+                    .line
+                    .saturating_sub(self.context_before + 1),
+                span.end_position.line + self.context_after,
+            ),
+            None => Vec::new(),
+        };
+        let mut rails = Rails::new(&report.labels);
+        let windows = self.visible_windows(report, &segment);
+        let shown_lines = match (windows.first(), windows.last()) {
+            (Some(first), Some(last)) => *first.start()..=*last.end(),
+            _ => RangeInclusive::new(1, 0),
+        };
+
+        let mut omitted_lines = 0;
+        for line in &segment {
+            if !shown_lines.contains(&line.line_number) {
+                continue;
+            }
+            if !windows
+                .iter()
+                .any(|window| window.contains(&line.line_number))
+            {
+                omitted_lines += 1;
+                continue;
+            }
+
+            if omitted_lines > 0 {
+                self.write_omitted_lines(print_stream, omitted_lines, max_number_of_digit, &rails)?;
+                omitted_lines = 0;
+            }
+
+            let line_labels = report
+                .labels
+                .iter()
+                .filter(|label| label.is_in(line.line_number))
+                .cloned()
+                .collect();
+
+            let mut message_column = 0;
+            for (index, group) in self
+                .window_groups(line, line_labels)
+                .into_iter()
+                .enumerate()
+            {
+                let (line, group) = &self.window_line(line, group);
+
+                let mut applied_labels: Vec<Label> = Vec::new();
+                let mut highlights: Vec<(usize, usize, Option<Color>)> = Vec::new();
+                let mut most_last_position = line.display_width(self.tab_width) + 1;
+
+                for label in group {
+                    let (start, end) = label.span.columns_on(line);
+                    highlights.push((start, end, label.format));
+
+                    if !label.is_multiline() {
+                        most_last_position = max(
+                            most_last_position,
+                            line.display_column(end, self.tab_width) + 2,
+                        );
+                        applied_labels.push(label.clone());
+                    }
+                }
+                message_column = max(message_column, most_last_position);
+
+                // A line split into several windows is drawn once per window,
+                // and only the first draws the rails starting or ending on it.
+                let rail_row = match index {
+                    0 => RailRow::Source(line.line_number),
+                    _ => RailRow::Continue(self.theme.character_set.vertical_bar),
+                };
+                self.write_line_number(print_stream, line.line_number, max_number_of_digit, false)?;
+                self.write_rails(print_stream, &rails, rail_row)?;
+                rails.start(line.line_number);
+
+                writeln!(print_stream, "{}", self.highlight_line(line, &highlights))?;
+
+                if !applied_labels.is_empty() {
+                    self.write_single_line_labels(
+                        print_stream,
+                        line,
+                        &applied_labels,
+                        most_last_position,
+                        max_number_of_digit,
+                        &rails,
+                    )?;
+                }
+            }
+
+            let mut ending = rails.ending_on(line.line_number);
+            while let Some(index) = ending.pop() {
+                self.write_virtual_line_prefix(print_stream, max_number_of_digit, &rails)?;
+                writeln!(print_stream)?;
+                self.write_line_number(print_stream, usize::MAX, max_number_of_digit, true)?;
+                self.write_rails(print_stream, &rails, RailRow::Close(index))?;
+                rails.active[index] = false;
+
+                let label = &rails.labels[index];
+                self.write_style(print_stream, Style::new().fg_color(label.format))?;
+                write!(
+                    print_stream,
+                    "{}{} {}",
+                    self.theme
+                        .character_set
+                        .horizontal_bar
+                        .to_string()
+                        .repeat(message_column),
+                    self.reset(),
+                    self.text(&label.message)
+                )?;
+
+                if let Some(hint) = &label.hint {
+                    writeln!(print_stream)?;
+                    self.write_virtual_line_prefix(print_stream, max_number_of_digit, &rails)?;
+                    write!(print_stream, "{}", " ".repeat(message_column))?;
+                    self.write_style(print_stream, self.theme.hint)?;
+                    write!(print_stream, "!hint: {}{}", self.text(hint), self.reset())?;
+                }
+
+                writeln!(print_stream)?;
+            }
+        }
+
+        if let (Some(error), Some(span)) = (own_span_error, own_span) {
+            self.write_invalid_span(print_stream, None, error, &span, max_number_of_digit)?;
+        }
+        for label in &invalid_labels {
+            let error = span_error(&label.span, &source).unwrap_or_default();
+            self.write_invalid_span(
+                print_stream,
+                Some(label),
+                error,
+                &label.span,
+                max_number_of_digit,
+            )?;
+        }
+
+        for suggestion in report.suggestions() {
+            self.write_suggestion(
+                print_stream,
+                &source,
+                suggestion,
+                max_number_of_digit,
+                &rails,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a row for a span that can't be drawn, with the message of its
+    /// label if it has one.
+    fn write_invalid_span(
+        &self,
+        print_stream: &mut dyn Write,
+        label: Option<&Label>,
+        error: &str,
+        span: &Span,
+        max_line_digit: usize,
+    ) -> io::Result<()> {
+        self.write_line_number(print_stream, usize::MAX, max_line_digit, true)?;
+        if let Some(label) = label {
+            self.write_style(print_stream, Style::new().fg_color(label.format))?;
+            write!(
+                print_stream,
+                "{}{} ",
+                self.text(&label.message),
+                self.reset()
+            )?;
+        }
+
+        self.write_style(print_stream, self.theme.gutter)?;
+        writeln!(
+            print_stream,
+            "({}: {}:{}-{}:{}){}",
+            error,
+            span.start_position.line,
+            span.start_position.column,
+            span.end_position.line,
+            span.end_position.column,
+            self.reset()
+        )
+    }
+
+    /// Returns the ranges of line numbers shown from `segment`: the lines
+    /// the report and its labels start and end on with their context, with
+    /// windows merged unless more than [`MAX_UNFOLDED_GAP`] lines lie between
+    /// them. Short segments are shown in full.
+    fn visible_windows(&self, report: &Report, segment: &[Line]) -> Vec<RangeInclusive<usize>> {
+        let (first, last) = match (segment.first(), segment.last()) {
+            (Some(first), Some(last)) => (first.line_number, last.line_number),
+            _ => return Vec::new(),
+        };
+
+        if last - first < self.full_span_lines {
+            return vec![first..=last];
+        }
+
+        let mut lines: Vec<usize> = report
+            .labels
+            .iter()
+            .map(|label| label.span)
+            .chain([report.common_span])
+            .flat_map(|span| [span.start_position.line, span.end_position.line])
+            .collect();
+        lines.sort();
+
+        let mut windows: Vec<RangeInclusive<usize>> = Vec::new();
+        for line in lines {
+            let start = line.saturating_sub(self.context_before).max(first);
+            let end = (line + self.context_after).min(last);
+
+            match windows.last_mut() {
+                Some(window) if start <= window.end() + MAX_UNFOLDED_GAP + 1 => {
+                    *window = *window.start()..=end.max(*window.end());
+                }
+                _ => windows.push(start..=end),
+            }
+        }
+
+        windows
+    }
+
+    /// Splits the labels on `line` into groups whose single-line labels fit
+    /// in one window of `max_width` cells together, so labels too far apart
+    /// each get a window of their own. Multi-line labels join the first
+    /// group.
+    fn window_groups(&self, line: &Line, labels: Vec<Label>) -> Vec<Vec<Label>> {
+        let content_width = match self.max_width {
+            Some(max_width) if line.display_width(self.tab_width) > max_width => {
+                max_width.max(3) - 2
+            }
+            _ => return vec![labels],
+        };
+
+        let (mut single_line, multi_line): (Vec<Label>, Vec<Label>) =
+            labels.into_iter().partition(|label| !label.is_multiline());
+        single_line.sort_by_key(|label| label.span.start_position);
+
+        let mut groups = vec![multi_line];
+        let mut group_start = None;
+        for label in single_line {
+            let (start, width) = self.display_columns(line, &label.span);
+            match group_start {
+                None => group_start = Some(start),
+                Some(first) if start + width.max(1) - first > content_width => {
+                    groups.push(Vec::new());
+                    group_start = Some(start);
+                }
+                Some(_) => {}
+            }
+            groups.last_mut().unwrap().push(label);
+        }
+
+        groups
+    }
+
+    /// Cuts `line` down to a window of at most `max_width` cells around the
+    /// columns of its single-line labels, marking the elided sides with an
+    /// ellipsis, and moves the labels' columns on the line to match. Tabs
+    /// inside the window are expanded to spaces.
+    fn window_line(&self, line: &Line, mut labels: Vec<Label>) -> (Line, Vec<Label>) {
+        let total_width = line.display_width(self.tab_width);
+        let max_width = match self.max_width {
+            Some(max_width) if total_width > max_width => max_width.max(3),
+            _ => return (line.clone(), labels),
+        };
+
+        let focus: Vec<usize> = labels
+            .iter()
+            .filter(|label| !label.is_multiline())
+            .flat_map(|label| {
+                let (start, end) = label.span.columns_on(line);
+                [start, end]
+            })
+            .map(|column| line.display_column(column, self.tab_width))
+            .collect();
+        let focus_start = focus.iter().min().copied().unwrap_or(0);
+        let focus_width = focus.iter().max().copied().unwrap_or(0) - focus_start;
+
+        let window_start = focus_start
+            .saturating_sub(max_width.saturating_sub(focus_width) / 2)
+            .min(total_width - max_width);
+        let window_end = window_start + max_width;
+        let content_start = window_start + usize::from(window_start > 0);
+        let content_end = window_end - usize::from(window_end < total_width);
+
+        let mut chars = String::new();
+        let mut char_count = 0;
+        if window_start > 0 {
+            chars.push(self.theme.character_set.horizontal_ellipsis);
+            char_count += 1;
+        }
+
+        let mut columns = Vec::new();
+        let mut display_column = 0;
+        for c in line.chars.chars() {
+            let width = char_width(c, display_column, self.tab_width);
+            columns.push(char_count);
+
+            if content_start <= display_column && display_column + width <= content_end {
+                if c == '\t' {
+                    chars.push_str(&" ".repeat(width));
+                    char_count += width;
+                } else {
+                    chars.push(c);
+                    char_count += 1;
+                }
+            }
+            display_column += width;
+        }
+        columns.push(char_count);
+
+        if window_end < total_width {
+            chars.push(self.theme.character_set.horizontal_ellipsis);
+        }
+
+        let last_column = columns.len() - 1;
+        for label in &mut labels {
+            for position in [&mut label.span.start_position, &mut label.span.end_position] {
+                if position.line == line.line_number {
+                    position.column = columns[position.column.min(last_column)];
+                }
+            }
+        }
+
+        (
+            Line::new(line.line_number, line.offset, chars.len(), chars),
+            labels,
+        )
+    }
+
+    fn write_omitted_lines(
+        &self,
+        print_stream: &mut dyn Write,
+        omitted_lines: usize,
+        max_line_digit: usize,
+        rails: &Rails,
+    ) -> io::Result<()> {
+        self.write_style(print_stream, self.theme.gutter)?;
+        write!(
+            print_stream,
+            "{} {} {}",
+            " ".repeat(max_line_digit),
+            self.theme.character_set.vertical_ellipsis,
+            self.reset(),
+        )?;
+        self.write_rails(
+            print_stream,
+            rails,
+            RailRow::Continue(self.theme.character_set.vertical_ellipsis),
+        )?;
+        self.write_style(print_stream, self.theme.gutter)?;
+        writeln!(
+            print_stream,
+            "lines omitted: {}{}",
+            omitted_lines,
+            self.reset()
+        )
+    }
+
+    /// Draws the underlines of the single-line labels on `line`, then one
+    /// message row per label. Every label gets its own anchor column where
+    /// possible, and messages are written rightmost anchor first, so a
+    /// connector never crosses the vertical bar of a label still waiting for
+    /// its message.
+    fn write_single_line_labels(
+        &self,
+        print_stream: &mut dyn Write,
+        line: &Line,
+        labels: &[Label],
+        message_column: usize,
+        max_line_digit: usize,
+        rails: &Rails,
+    ) -> io::Result<()> {
+        let columns: Vec<(usize, usize)> = labels
+            .iter()
+            .map(|label| {
+                let (start, width) = self.display_columns(line, &label.span);
+                (start, start + width.max(1))
+            })
+            .collect();
+
+        let mut by_width: Vec<usize> = (0..labels.len()).collect();
+        by_width.sort_by_key(|&index| (columns[index].1 - columns[index].0, columns[index].0));
+
+        let mut anchors = vec![0; labels.len()];
+        let mut taken = Vec::new();
+        for index in by_width {
+            let (start, end) = columns[index];
+            let middle = start + (end - start) / 2;
+            let anchor = (start..end)
+                .filter(|column| !taken.contains(column))
+                .min_by_key(|column| column.abs_diff(middle))
+                .unwrap_or(middle);
+            taken.push(anchor);
+            anchors[index] = anchor;
+        }
+
+        let width = columns.iter().map(|(_, end)| *end).max().unwrap_or(0);
+        let mut underline = vec![(' ', None); width];
+        for (column, cell) in underline.iter_mut().enumerate() {
+            let covering = columns
+                .iter()
+                .zip(labels)
+                .filter(|((start, end), _)| *start <= column && column < *end)
+                .min_by_key(|((start, end), _)| end - start);
+            if let Some((_, label)) = covering {
+                *cell = (self.theme.character_set.underline, label.format);
+            }
+        }
+        for (anchor, label) in anchors.iter().zip(labels) {
+            underline[*anchor] = (self.theme.character_set.under_bar, label.format);
+        }
+
+        self.write_virtual_line_prefix(print_stream, max_line_digit, rails)?;
+        self.write_cells(print_stream, trim_cells(&underline))?;
+        writeln!(print_stream)?;
+
+        let mut pending: Vec<usize> = (0..labels.len()).collect();
+        pending.sort_by_key(|&index| anchors[index]);
+
+        while let Some(index) = pending.pop() {
+            let label = &labels[index];
+            let mut row = self.vertical_bars(&pending, &anchors, labels, message_column);
+            row[anchors[index]] = (self.theme.character_set.left_bottom, label.format);
+            for cell in &mut row[anchors[index] + 1..] {
+                *cell = (self.theme.character_set.horizontal_bar, label.format);
+            }
+
+            self.write_virtual_line_prefix(print_stream, max_line_digit, rails)?;
+            self.write_cells(print_stream, &row)?;
+            writeln!(print_stream, " {}", self.text(&label.message))?;
+
+            if let Some(hint) = &label.hint {
+                let row = self.vertical_bars(&pending, &anchors, labels, 0);
+                self.write_virtual_line_prefix(print_stream, max_line_digit, rails)?;
+                let row = trim_cells(&row);
+                self.write_cells(print_stream, row)?;
+                write!(
+                    print_stream,
+                    "{}",
+                    " ".repeat(message_column.saturating_sub(row.len()))
+                )?;
+                self.write_style(print_stream, self.theme.hint)?;
+                writeln!(print_stream, "!hint: {}{}", self.text(hint), self.reset())?;
+            }
+
+            let row = self.vertical_bars(&pending, &anchors, labels, 0);
+            self.write_virtual_line_prefix(print_stream, max_line_digit, rails)?;
+            self.write_cells(print_stream, trim_cells(&row))?;
+            writeln!(print_stream)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns a row holding the vertical bar of every label in `pending`,
+    /// padded with spaces to at least `width` cells.
+    fn vertical_bars(
+        &self,
+        pending: &[usize],
+        anchors: &[usize],
+        labels: &[Label],
+        width: usize,
+    ) -> Vec<(char, Option<Color>)> {
+        let width = pending
+            .iter()
+            .map(|&index| anchors[index] + 1)
+            .fold(width, max);
+        let mut row = vec![(' ', None); width];
+        for &index in pending {
+            row[anchors[index]] = (self.theme.character_set.vertical_bar, labels[index].format);
+        }
+        row
+    }
+
+    /// Writes a row of cells, styling each run of cells sharing a color.
+    fn write_cells(
+        &self,
+        print_stream: &mut dyn Write,
+        cells: &[(char, Option<Color>)],
+    ) -> io::Result<()> {
+        for run in cells.chunk_by(|a, b| a.1 == b.1) {
+            let text: String = run.iter().map(|(c, _)| c).collect();
+            match run[0].1 {
+                Some(color) => {
+                    self.write_style(print_stream, Style::new().fg_color(Some(color)))?;
+                    write!(print_stream, "{}{}", self.text(&text), self.reset())?;
+                }
+                None => write!(print_stream, "{}", self.text(&text))?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_virtual_line_prefix(
+        &self,
+        print_stream: &mut dyn Write,
+        max_line_digit: usize,
+        rails: &Rails,
+    ) -> io::Result<()> {
+        self.write_line_number(print_stream, usize::MAX, max_line_digit, true)?;
+        self.write_rails(
+            print_stream,
+            rails,
+            RailRow::Continue(self.theme.character_set.vertical_bar),
+        )
+    }
+
+    fn write_suggestion(
+        &self,
+        print_stream: &mut dyn Write,
+        source: &Source,
+        suggestion: &Suggestion,
+        max_line_digit: usize,
+        rails: &Rails,
+    ) -> io::Result<()> {
+        let (before, after) = match suggestion.diff(source) {
+            Some(diff) => diff,
+            None => return Ok(()),
+        };
+
+        self.write_line_number(print_stream, usize::MAX, max_line_digit, true)?;
+        writeln!(print_stream)?;
+        self.write_line_number(print_stream, usize::MAX, max_line_digit, true)?;
+        self.write_style(print_stream, self.theme.hint)?;
+        writeln!(
+            print_stream,
+            "suggestion ({}):{}",
+            suggestion.applicability.name(),
+            self.reset()
+        )?;
+
+        let line_number = suggestion.span.start_position.line;
+        for (sign, style, text) in [
+            ('-', self.theme.removed, before),
+            ('+', self.theme.added, after),
+        ] {
+            for (index, chars) in text.split('\n').enumerate() {
+                let line = Line::new(line_number + index, 0, chars.len(), chars.to_string());
+
+                self.write_style(print_stream, self.theme.gutter)?;
+                write!(
+                    print_stream,
+                    "{:width$} {}",
+                    line.line_number,
+                    self.reset(),
+                    width = max_line_digit
+                )?;
+                self.write_style(print_stream, style)?;
+                write!(print_stream, "{}{} ", sign, self.reset())?;
+                self.write_rails(print_stream, rails, RailRow::Continue(' '))?;
+                writeln!(
+                    print_stream,
+                    "{}",
+                    self.highlight_line(&line, &[(0, usize::MAX, style.get_fg_color())])
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a footer as `= note: message`, indenting the continuation lines
+    /// of a multi-line message to line up with its first line.
+    fn write_footer(
+        &self,
+        print_stream: &mut dyn Write,
+        footer: &Footer,
+        max_line_digit: usize,
+    ) -> io::Result<()> {
+        let name = footer.report_type.name();
+        let indent = " ".repeat(max_line_digit + name.len() + 5);
+
+        self.write_style(print_stream, self.theme.gutter)?;
+        write!(
+            print_stream,
+            "{:width$} = {}",
+            "",
+            self.reset(),
+            width = max_line_digit
+        )?;
+        self.write_style(print_stream, self.theme.header_style(footer.report_type))?;
+        write!(print_stream, "{}:{}", name, self.reset())?;
+
+        for (index, line) in footer.message.split('\n').enumerate() {
+            if index == 0 {
+                writeln!(print_stream, " {}", self.text(line))?;
+            } else {
+                writeln!(print_stream, "{}{}", indent, self.text(line))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_line_number(
+        &self,
+        print_stream: &mut dyn Write,
+        line_number: usize,
+        max_line_digit: usize,
+        is_virtual_line: bool,
+    ) -> io::Result<()> {
+        self.write_style(print_stream, self.theme.gutter)?;
+        if is_virtual_line {
+            write!(
+                print_stream,
+                "{:width$} {} ",
+                "",
+                self.theme.character_set.vertical_bar_breaking,
+                width = max_line_digit
+            )?;
+        } else {
+            write!(
+                print_stream,
+                "{:width$} {} ",
+                line_number,
+                self.theme.character_set.vertical_bar,
+                width = max_line_digit
+            )?;
+        }
+        write!(print_stream, "{}", self.reset())
+    }
+
+    fn write_rails(
+        &self,
+        print_stream: &mut dyn Write,
+        rails: &Rails,
+        row: RailRow,
+    ) -> io::Result<()> {
+        self.write_cells(print_stream, &self.rail_cells(rails, row))
+    }
+
+    /// Lays out the rail area of a row. A connector runs from the leftmost
+    /// rail starting, ending or closing on the row to the right edge of the
+    /// area, crossing or joining the rails it passes.
+    fn rail_cells(&self, rails: &Rails, row: RailRow) -> Vec<(char, Option<Color>)> {
+        let count = rails.labels.len();
+        let mut cells = vec![(' ', None); count * 2 + 3];
+        let is_event = |index: usize| {
+            let span = &rails.labels[index].span;
+            match row {
+                RailRow::Source(line_number) => {
+                    span.start_position.line == line_number || span.end_position.line == line_number
+                }
+                RailRow::Close(closing) => index == closing,
+                RailRow::Continue(_) => false,
+            }
+        };
+        let connector = (0..count).find(|&index| is_event(index));
+        let vertical_bar = match row {
+            RailRow::Continue(variant) => variant,
+            _ => self.theme.character_set.vertical_bar,
+        };
+
+        for (index, label) in rails.labels.iter().enumerate() {
+            let starts = !rails.active[index] && is_event(index);
+            let glyph = match connector {
+                Some(connector) if index == connector => match row {
+                    RailRow::Close(_) => self.theme.character_set.left_bottom,
+                    _ if starts => self.theme.character_set.left_top,
+                    _ => self.theme.character_set.left_cross,
+                },
+                Some(connector) if index > connector => {
+                    if starts {
+                        self.theme.character_set.under_bar
+                    } else if rails.active[index] {
+                        self.theme.character_set.cross_bar
+                    } else {
+                        cells[index * 2] = (
+                            self.theme.character_set.horizontal_bar,
+                            rails.labels[connector].format,
+                        );
+                        continue;
+                    }
+                }
+                _ if rails.active[index] => vertical_bar,
+                _ => continue,
+            };
+            cells[index * 2] = (glyph, label.format);
+        }
+
+        if let Some(connector) = connector {
+            let color = rails.labels[connector].format;
+            for cell in &mut cells[connector * 2 + 1..] {
+                if cell.0 == ' ' {
+                    *cell = (self.theme.character_set.horizontal_bar, color);
+                }
+            }
+            if let RailRow::Source(_) = row {
+                cells[count * 2 + 1] = (self.theme.character_set.right_arrow, color);
+                cells[count * 2 + 2] = (' ', None);
+            }
+        }
+
+        cells
+    }
+
+    fn write_source_location(
+        &self,
+        print_stream: &mut dyn Write,
+        max_line_digit: usize,
+        file_id: FileId,
+        is_first_section: bool,
+        start_position: &Position,
+    ) -> io::Result<()> {
+        self.write_style(print_stream, self.theme.gutter)?;
+        write!(
+            print_stream,
+            "{:>width$}{}[",
+            if is_first_section {
+                self.theme.character_set.left_top
+            } else {
+                self.theme.character_set.left_cross
+            },
+            self.theme.character_set.horizontal_bar,
+            width = max_line_digit + 2
+        )?;
+        write!(
+            print_stream,
+            "{}{}:{}:{}",
+            self.reset(),
+            self.text(&self.source_map.display_name(file_id)),
+            start_position.line,
+            start_position.column
+        )?;
+        self.write_style(print_stream, self.theme.gutter)?;
+        writeln!(print_stream, "]{}", self.reset())
+    }
+
+    fn highlight_line(&self, line: &Line, highlights: &[(usize, usize, Option<Color>)]) -> String {
+        let mut line_builder = String::new();
+        let mut current_color = None;
+        let mut display_column = 0;
+
+        for (column, c) in line.chars.chars().enumerate() {
+            let color = highlights
+                .iter()
+                .filter(|(start, end, _)| *start <= column && column < *end)
+                .min_by_key(|(start, end, _)| end - start)
+                .and_then(|(_, _, color)| *color);
+
+            if color != current_color {
+                if current_color.is_some() {
+                    line_builder.push_str(&self.reset());
+                }
+                if let Some(color) = color {
+                    line_builder.push_str(&self.style(Style::new().fg_color(Some(color))));
+                }
+                current_color = color;
+            }
+
+            let width = char_width(c, display_column, self.tab_width);
+            if c == '\t' {
+                line_builder.push_str(&" ".repeat(width));
+            } else {
+                line_builder.push_str(&self.text(c.encode_utf8(&mut [0; 4])));
+            }
+            display_column += width;
+        }
+
+        if current_color.is_some() {
+            line_builder.push_str(&self.reset());
+        }
+
+        line_builder
+    }
+
+    fn write_style(&self, print_stream: &mut dyn Write, style: Style) -> io::Result<()> {
+        write!(print_stream, "{}", self.style(style))
+    }
+
+    fn style(&self, style: Style) -> String {
+        match self.markup {
+            Markup::Plain => String::new(),
+            Markup::Ansi => style.render().to_string(),
+            Markup::Html => html::open_span(&style),
+        }
+    }
+
+    fn reset(&self) -> String {
+        match self.markup {
+            Markup::Plain => String::new(),
+            Markup::Ansi => Reset.render().to_string(),
+            Markup::Html => "</span>".to_string(),
+        }
+    }
+
+    fn text<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        match self.markup {
+            Markup::Html => html::escape(text),
+            _ => Cow::Borrowed(text),
+        }
+    }
+
+    fn display_columns(&self, line: &Line, span: &Span) -> (usize, usize) {
+        let (start, end) = span.columns_on(line);
+        let start = line.display_column(start, self.tab_width);
+        let end = line.display_column(end, self.tab_width);
+
+        (start, end.saturating_sub(start))
+    }
+}
+
+/// Returns why `span` can't be drawn from `source`, if it can't.
+fn span_error(span: &Span, source: &Source) -> Option<&'static str> {
+    if span.start_position > span.end_position {
+        Some("reversed span")
+    } else if [span.start_position, span.end_position]
+        .iter()
+        .any(|position| match source.line(position.line) {
+            Some(line) => position.column > line.chars.chars().count(),
+            None => true,
+        })
+    {
+        Some("span out of range")
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RailRow {
+    /// The row of a source line, where rails start and end.
+    Source(usize),
+    /// A row between source lines, drawing active rails with the given glyph.
+    Continue(char),
+    /// The row closing a rail, which its message follows.
+    Close(usize),
+}
+
+/// The multi-line labels of a snippet, each drawn on its own rail. Rails are
+/// ordered by where their label starts, outermost first, so a label nested in
+/// another always sits to the right of it.
+struct Rails {
+    labels: Vec<Label>,
+    active: Vec<bool>,
+}
+
+impl Rails {
+    fn new(labels: &[Label]) -> Self {
+        let mut labels: Vec<Label> = labels
+            .iter()
+            .filter(|label| label.is_multiline())
+            .cloned()
+            .collect();
+        labels.sort_by_key(|label| (label.span.start_position, Reverse(label.span.end_position)));
+
+        Self {
+            active: vec![false; labels.len()],
+            labels,
+        }
+    }
+
+    /// Activates the rails of the labels starting on `line_number`.
+    fn start(&mut self, line_number: usize) {
+        for (label, active) in self.labels.iter().zip(&mut self.active) {
+            if label.span.start_position.line == line_number {
+                *active = true;
+            }
+        }
+    }
+
+    fn ending_on(&self, line_number: usize) -> Vec<usize> {
+        (0..self.labels.len())
+            .filter(|&index| {
+                self.active[index] && self.labels[index].span.end_position.line == line_number
+            })
+            .collect()
+    }
+}
+
+/// Drops the trailing blank cells of a row.
+fn trim_cells(cells: &[(char, Option<Color>)]) -> &[(char, Option<Color>)] {
+    let length = cells
+        .iter()
+        .rposition(|cell| *cell != (' ', None))
+        .map_or(0, |index| index + 1);
+    &cells[..length]
+}
@@ -0,0 +1,178 @@
+use super::{
+    emitter::Emitter,
+    human_emitter::HumanEmitter,
+    label::Label,
+    report::{Footer, Report},
+    source_map::{file_name, SourceMap},
+    span::Span,
+    suggestion::Suggestion,
+    theme::{color_name, Theme},
+};
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// One report in the shape of rustc's `--error-format=json`. Lines and
+/// columns are 1-based, columns count chars.
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonDiagnostic {
+    pub tag: String,
+    pub severity: &'static str,
+    pub message: String,
+    pub span: JsonSpan,
+    pub labels: Vec<JsonLabel>,
+    pub footers: Vec<JsonFooter>,
+    pub children: Vec<JsonDiagnostic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rendered: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonSpan {
+    pub file_name: String,
+    pub byte_start: Option<usize>,
+    pub byte_end: Option<usize>,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonLabel {
+    pub span: JsonSpan,
+    pub message: String,
+    pub hint: Option<String>,
+    pub color: Option<String>,
+    pub suggestions: Vec<JsonSuggestion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonFooter {
+    pub severity: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonSuggestion {
+    pub span: JsonSpan,
+    pub replacement: String,
+    pub applicability: &'static str,
+}
+
+impl JsonDiagnostic {
+    /// Converts a resolved report, naming files through `source_map`.
+    pub fn new(report: &Report, source_map: &SourceMap, rendered: Option<String>) -> Self {
+        Self {
+            tag: report.tag.clone(),
+            severity: report.report_type.name(),
+            message: report.message.clone(),
+            span: JsonSpan::new(&report.common_span, &file_name(source_map, report.file_id)),
+            labels: report
+                .labels
+                .iter()
+                .map(|label| JsonLabel::new(label, &file_name(source_map, label.file_id)))
+                .collect(),
+            footers: report.footers.iter().map(JsonFooter::new).collect(),
+            children: report
+                .children
+                .iter()
+                .map(|child| JsonDiagnostic::new(child, source_map, None))
+                .collect(),
+            rendered,
+        }
+    }
+}
+
+impl JsonSpan {
+    pub fn new(span: &Span, file_name: &str) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            byte_start: span.offsets.map(|(start, _)| start),
+            byte_end: span.offsets.map(|(_, end)| end),
+            line_start: span.start_position.line,
+            line_end: span.end_position.line,
+            column_start: span.start_position.column + 1,
+            column_end: span.end_position.column + 1,
+        }
+    }
+}
+
+impl JsonLabel {
+    pub fn new(label: &Label, file_name: &str) -> Self {
+        Self {
+            span: JsonSpan::new(&label.span, file_name),
+            message: label.message.clone(),
+            hint: label.hint.clone(),
+            color: label.format.map(color_name),
+            suggestions: label
+                .suggestions
+                .iter()
+                .map(|suggestion| JsonSuggestion::new(suggestion, file_name))
+                .collect(),
+        }
+    }
+}
+
+impl JsonFooter {
+    pub fn new(footer: &Footer) -> Self {
+        Self {
+            severity: footer.report_type.name(),
+            message: footer.message.clone(),
+        }
+    }
+}
+
+impl JsonSuggestion {
+    pub fn new(suggestion: &Suggestion, file_name: &str) -> Self {
+        Self {
+            span: JsonSpan::new(&suggestion.span, file_name),
+            replacement: suggestion.replacement.clone(),
+            applicability: suggestion.applicability.name(),
+        }
+    }
+}
+
+/// Writes one [`JsonDiagnostic`] per line. With a human emitter, each object
+/// also carries that emitter's output in its `rendered` field.
+#[derive(Debug, Clone, Default)]
+pub struct JsonEmitter {
+    pub rendered: Option<HumanEmitter>,
+}
+
+impl JsonEmitter {
+    pub fn new(rendered: Option<HumanEmitter>) -> Self {
+        Self { rendered }
+    }
+}
+
+impl Emitter for JsonEmitter {
+    fn emit(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        reports: &[Report],
+        colored: bool,
+    ) -> io::Result<()> {
+        for report in reports {
+            let rendered = match &self.rendered {
+                Some(human_emitter) => {
+                    let mut buffer = Vec::new();
+                    human_emitter.render_report(&mut buffer, source_map, report, colored)?;
+                    Some(String::from_utf8_lossy(&buffer).to_string())
+                }
+                None => None,
+            };
+
+            let diagnostic = JsonDiagnostic::new(report, source_map, rendered);
+            writeln!(print_stream, "{}", serde_json::to_string(&diagnostic)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn active_theme(&self) -> Option<&Theme> {
+        self.rendered
+            .as_ref()
+            .map(|human_emitter| &human_emitter.theme)
+    }
+}
@@ -1,21 +1,25 @@
-use super::span::Span;
+use super::{source_map::FileId, span::Span, suggestion::Suggestion};
 use anstyle::Color;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Label {
+    pub file_id: Option<FileId>,
     pub format: Option<Color>,
     pub span: Span,
     pub message: String,
     pub hint: Option<String>,
+    pub suggestions: Vec<Suggestion>,
 }
 
 impl Label {
     pub fn new(span: Span, message: String) -> Self {
         Self {
+            file_id: None,
             format: None,
             span,
             message,
             hint: None,
+            suggestions: Vec::new(),
         }
     }
 
@@ -29,6 +33,11 @@ impl Label {
         self.clone()
     }
 
+    pub fn add_suggestion(&mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self.clone()
+    }
+
     pub fn is_multiline(&self) -> bool {
         self.span.is_multiline()
     }
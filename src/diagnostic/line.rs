@@ -1,4 +1,5 @@
 use super::{position::Position, span::Span};
+use unicode_width::UnicodeWidthChar;
 
 #[derive(Debug, Clone)]
 pub struct Line {
@@ -19,9 +20,42 @@ impl Line {
     }
 
     pub fn span(&self) -> Span {
-        Span::new(
-            Position::new(self.line_number, self.offset),
-            Position::new(self.line_number, self.offset + self.length),
-        )
+        Span {
+            start_position: Position::new(self.line_number, 0),
+            end_position: Position::new(self.line_number, self.chars.chars().count()),
+            offsets: Some((self.offset, self.offset + self.length)),
+        }
+    }
+
+    /// Converts a column in chars into the terminal column it is drawn at.
+    /// Columns past the end of the line count one cell each.
+    pub fn display_column(&self, column: usize, tab_width: usize) -> usize {
+        let mut display_column = 0;
+        let mut chars = self.chars.chars();
+
+        for _ in 0..column {
+            display_column += match chars.next() {
+                Some(c) => char_width(c, display_column, tab_width),
+                None => 1,
+            };
+        }
+
+        display_column
+    }
+
+    pub fn display_width(&self, tab_width: usize) -> usize {
+        self.display_column(self.chars.chars().count(), tab_width)
+    }
+}
+
+/// Returns how many terminal cells `c` occupies when drawn at
+/// `display_column`. Tabs advance to the next tab stop, wide chars take two
+/// cells and combining marks and control chars take none.
+pub fn char_width(c: char, display_column: usize, tab_width: usize) -> usize {
+    if c == '\t' {
+        let tab_width = tab_width.max(1);
+        tab_width - display_column % tab_width
+    } else {
+        c.width().unwrap_or(0)
     }
 }
@@ -1,8 +1,21 @@
 pub mod char_set;
+pub mod codes;
+pub mod color_mode;
+pub mod emitter;
+pub mod github;
+pub mod html;
+pub mod human_emitter;
+pub mod json;
 pub mod label;
 pub mod line;
 pub mod position;
 pub mod report;
 pub mod report_builder;
+pub mod sarif;
+pub mod short;
+pub mod sink;
 pub mod source;
+pub mod source_map;
 pub mod span;
+pub mod suggestion;
+pub mod theme;
@@ -1,17 +1,93 @@
-use super::{label::Label, span::Span};
+use super::{
+    codes::ErrorCode,
+    label::Label,
+    source_map::{FileId, SourceMap},
+    span::Span,
+    suggestion::Suggestion,
+};
+use anstyle::{AnsiColor, Color, Effects, Style};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(C)]
 pub enum ReportType {
     Error,
     Warning,
+    Note,
+    Help,
+    Info,
+    Bug,
 }
 
-#[derive(Debug, Clone)]
+impl ReportType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReportType::Error => "error",
+            ReportType::Warning => "warning",
+            ReportType::Note => "note",
+            ReportType::Help => "help",
+            ReportType::Info => "info",
+            ReportType::Bug => "bug",
+        }
+    }
+
+    pub fn default_tag(&self) -> &'static str {
+        match self {
+            ReportType::Error => "error",
+            ReportType::Warning => "warning",
+            ReportType::Note => "note",
+            ReportType::Help => "help",
+            ReportType::Info => "info",
+            ReportType::Bug => "internal compiler error",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        Color::Ansi(match self {
+            ReportType::Error => AnsiColor::Red,
+            ReportType::Warning => AnsiColor::Yellow,
+            ReportType::Note => AnsiColor::Green,
+            ReportType::Help => AnsiColor::Cyan,
+            ReportType::Info => AnsiColor::Blue,
+            ReportType::Bug => AnsiColor::Magenta,
+        })
+    }
+
+    pub fn header_style(&self) -> Style {
+        let style = Style::new().fg_color(Some(self.color()));
+
+        match self {
+            ReportType::Bug => style.effects(Effects::BOLD | Effects::UNDERLINE),
+            _ => style,
+        }
+    }
+}
+
+/// A trailing line of a report such as `= note: ...`, rendered below its
+/// snippets in the order it was added.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Footer {
+    pub report_type: ReportType,
+    pub message: String,
+}
+
+impl Footer {
+    pub fn new(report_type: ReportType, message: String) -> Self {
+        Self {
+            report_type,
+            message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Report {
+    pub file_id: Option<FileId>,
     pub tag: String,
+    pub code: Option<&'static ErrorCode>,
     pub common_span: Span,
     pub labels: Vec<Label>,
+    pub footers: Vec<Footer>,
+    pub children: Vec<Report>,
     pub report_type: ReportType,
     pub message: String,
 }
@@ -19,32 +95,145 @@ pub struct Report {
 impl Report {
     pub fn new(tag: Option<String>, span: Span, report_type: ReportType, message: String) -> Self {
         Self {
-            tag: if tag.is_some() {
-                tag.unwrap()
-            } else {
-                "".to_string()
-            },
+            file_id: None,
+            tag: tag.unwrap_or_default(),
+            code: None,
             common_span: span,
             labels: vec![],
+            footers: vec![],
+            children: vec![],
             report_type,
             message,
         }
     }
 
-    pub fn error(span: Span, message: String) -> Self {
-        Self::new(Some("error".to_string()), span, ReportType::Error, message)
-    }
-
-    pub fn warning(span: Span, message: String) -> Self {
+    pub fn with_type(report_type: ReportType, span: Span, message: String) -> Self {
         Self::new(
-            Some("warning".to_string()),
+            Some(report_type.default_tag().to_string()),
             span,
-            ReportType::Warning,
+            report_type,
             message,
         )
     }
 
+    pub fn from_code(code: &'static ErrorCode, span: Span, message: String) -> Self {
+        let mut report = Self::new(Some(code.code.to_string()), span, code.report_type, message);
+        report.code = Some(code);
+        report
+    }
+
+    pub fn error(span: Span, message: String) -> Self {
+        Self::with_type(ReportType::Error, span, message)
+    }
+
+    pub fn warning(span: Span, message: String) -> Self {
+        Self::with_type(ReportType::Warning, span, message)
+    }
+
+    pub fn note(span: Span, message: String) -> Self {
+        Self::with_type(ReportType::Note, span, message)
+    }
+
+    pub fn help(span: Span, message: String) -> Self {
+        Self::with_type(ReportType::Help, span, message)
+    }
+
+    pub fn info(span: Span, message: String) -> Self {
+        Self::with_type(ReportType::Info, span, message)
+    }
+
+    pub fn bug(span: Span, message: String) -> Self {
+        Self::with_type(ReportType::Bug, span, message)
+    }
+
     pub fn add_label(&mut self, label: Label) {
         self.labels.push(label);
     }
+
+    pub fn add_footer(&mut self, footer: Footer) {
+        self.footers.push(footer);
+    }
+
+    pub fn add_child(&mut self, child: Report) {
+        self.children.push(child);
+    }
+
+    /// Returns the report followed by all of its children, depth first.
+    pub fn flatten(&self) -> Vec<&Report> {
+        let mut reports = vec![self];
+        for child in &self.children {
+            reports.extend(child.flatten());
+        }
+        reports
+    }
+
+    /// Ties the report, its labels and its children to `file_id` unless they
+    /// name a file of their own, then resolves every span against its file.
+    pub fn resolve_spans(&mut self, source_map: &SourceMap, file_id: FileId) {
+        let file_id = *self.file_id.get_or_insert(file_id);
+        if let Some(source) = source_map.get(file_id) {
+            self.common_span = self.common_span.resolve(&source);
+        }
+
+        for label in &mut self.labels {
+            let label_file_id = *label.file_id.get_or_insert(file_id);
+            let source = match source_map.get(label_file_id) {
+                Some(source) => source,
+                None => continue,
+            };
+
+            label.span = label.span.resolve(&source);
+            for suggestion in &mut label.suggestions {
+                suggestion.span = suggestion.span.resolve(&source);
+            }
+        }
+
+        for child in &mut self.children {
+            child.resolve_spans(source_map, file_id);
+        }
+    }
+
+    /// Returns the files the report touches, its own file first.
+    pub fn files(&self) -> Vec<FileId> {
+        let mut files: Vec<FileId> = self.file_id.into_iter().collect();
+        for label in &self.labels {
+            if let Some(file_id) = label.file_id {
+                if !files.contains(&file_id) {
+                    files.push(file_id);
+                }
+            }
+        }
+        files
+    }
+
+    pub fn suggestions(&self) -> impl Iterator<Item = &Suggestion> {
+        self.labels.iter().flat_map(|label| &label.suggestions)
+    }
+
+    /// Gives every label without an explicit color the palette entry for its
+    /// index, so the same report always renders with the same colors.
+    pub fn assign_colors(&mut self, palette: &[Color]) {
+        if palette.is_empty() {
+            return;
+        }
+
+        for (index, label) in self.labels.iter_mut().enumerate() {
+            if label.format.is_none() {
+                label.format = Some(palette[index % palette.len()]);
+            }
+        }
+
+        for child in &mut self.children {
+            child.assign_colors(palette);
+        }
+    }
+
+    /// Orders the labels of the report and its children by start column.
+    pub fn sort_labels(&mut self) {
+        self.labels.sort_by_key(|label| label.span.start_position);
+
+        for child in &mut self.children {
+            child.sort_labels();
+        }
+    }
 }
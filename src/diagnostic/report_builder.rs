@@ -1,60 +1,92 @@
 use super::{
-    char_set::{CharacterSet, UNICODE},
-    label::{self, Label},
-    position::Position,
-    report::{Report, ReportType},
-    source::{Source, SOURCE_CACHE},
+    char_set::CharacterSet,
+    codes::ErrorCode,
+    color_mode::ColorMode,
+    emitter::{Emitter, ErrorFormat},
+    github::GithubEmitter,
+    html::HtmlEmitter,
+    human_emitter::HumanEmitter,
+    json::JsonEmitter,
+    label::Label,
+    report::{Footer, Report, ReportType},
+    sarif::SarifEmitter,
+    short::ShortEmitter,
+    sink::DiagnosticSink,
+    source::BOM,
+    source_map::{FileId, SourceMap},
     span::Span,
+    suggestion::{apply_suggestions, Applicability, Suggestion},
+    theme::Theme,
 };
-use anstyle::{AnsiColor, Color, Reset, Style};
-use ilog::IntLog;
-use rand::Rng;
+use anstyle::{AnsiColor, Color};
 use std::{
-    cmp::max,
-    collections::HashMap,
-    path::{Path, PathBuf},
+    fmt::{self, Display, Formatter},
+    io::{self, IsTerminal, Write},
+    path::Path,
+    sync::Arc,
 };
 
+pub const LABEL_PALETTE: [Color; 7] = [
+    Color::Ansi(AnsiColor::BrightRed),
+    Color::Ansi(AnsiColor::BrightGreen),
+    Color::Ansi(AnsiColor::BrightYellow),
+    Color::Ansi(AnsiColor::BrightBlue),
+    Color::Ansi(AnsiColor::BrightMagenta),
+    Color::Ansi(AnsiColor::BrightCyan),
+    Color::Ansi(AnsiColor::BrightWhite),
+];
+
 #[derive(Debug, Clone)]
 pub struct FileReportBuilder {
-    file_path: Option<String>,
-    source_name: String,
-    source_file: Option<PathBuf>,
-    source: Option<String>,
-    enable_color: bool,
-    character_set: CharacterSet,
+    source_map: Arc<SourceMap>,
+    file_id: FileId,
+    color_mode: ColorMode,
+    error_format: ErrorFormat,
+    emitter: Option<Arc<dyn Emitter>>,
+    theme: Theme,
+    tab_width: usize,
+    context_lines: (usize, usize),
+    full_span_lines: usize,
+    max_width: Option<usize>,
     reports: Vec<Report>,
 }
 
 impl FileReportBuilder {
-    pub fn new_file(source_file: &Path) -> FileReportBuilder {
-        let file_path = source_file.to_string_lossy().to_string();
-
+    pub fn new(source_map: Arc<SourceMap>, file_id: FileId) -> FileReportBuilder {
         FileReportBuilder {
-            file_path: Some(file_path.clone()),
-            source_name: file_path.clone(),
-            source_file: Some(source_file.to_path_buf()),
-            source: None,
-            enable_color: true,
-            character_set: UNICODE.clone(),
+            source_map,
+            file_id,
+            color_mode: ColorMode::default(),
+            error_format: ErrorFormat::default(),
+            emitter: None,
+            theme: Theme::default(),
+            tab_width: 4,
+            context_lines: (1, 1),
+            full_span_lines: 0,
+            max_width: None,
             reports: Vec::new(),
         }
     }
 
+    pub fn new_file(source_file: &Path) -> FileReportBuilder {
+        let source_map = Arc::new(SourceMap::new());
+        let file_id = source_map.add_path(source_file);
+
+        FileReportBuilder::new(source_map, file_id)
+    }
+
     pub fn new_source(source: String) -> FileReportBuilder {
-        FileReportBuilder {
-            file_path: None,
-            source_name: "Unknown".to_string(),
-            source_file: None,
-            source: Some(if source.ends_with('\n') {
+        let source_map = Arc::new(SourceMap::new());
+        let file_id = source_map.add_source(
+            "Unknown".to_string(),
+            if source.ends_with('\n') {
                 source
             } else {
                 source + "\n"
-            }),
-            enable_color: true,
-            character_set: UNICODE.clone(),
-            reports: Vec::new(),
-        }
+            },
+        );
+
+        FileReportBuilder::new(source_map, file_id)
     }
 
     pub fn source_file(source_file: &Path) -> FileReportBuilder {
@@ -65,617 +97,136 @@ impl FileReportBuilder {
         FileReportBuilder::new_source(source)
     }
 
-    pub fn print(&mut self, print_stream: &mut dyn std::io::Write) {
-        let source: Source;
-        unsafe {
-            let mut cache = SOURCE_CACHE.clone().unwrap();
-
-            if let Some(source_file) = &self.source_file {
-                match cache.insert(
-                    source_file.clone(),
-                    Source::from_pathbuf(source_file.to_path_buf()).unwrap(),
-                ) {
-                    Some(src) => {
-                        source = src;
-                    }
-                    None => {
-                        source = cache.get(source_file).unwrap().clone();
-                    }
-                }
-            } else {
-                source = Source::from_string(self.source.clone().unwrap());
-            }
-        }
+    pub fn source_map(&self) -> &Arc<SourceMap> {
+        &self.source_map
+    }
 
-        for report in &mut self.reports.clone() {
-            report.labels.sort_by(|a, b| {
-                a.span
-                    .start_position
-                    .column
-                    .cmp(&b.span.start_position.column)
-            });
-            let max_number_of_digit = max(
-                usize::log10(report.common_span.start_position.line) + 1,
-                usize::log10(report.common_span.end_position.line) + 1,
-            ) as usize;
-
-            let mut occupied_multiline_labels = HashMap::<Label, bool>::new();
-            let segment = source.sub_list(
-                report.common_span.start_position.line - 1,
-                report.common_span.end_position.line,
-            );
-
-            match report.report_type {
-                ReportType::Error => Style::new()
-                    .fg_color(Some(Color::Ansi(AnsiColor::Red)))
-                    .write_to(print_stream)
-                    .unwrap(),
-                ReportType::Warning => Style::new()
-                    .fg_color(Some(Color::Ansi(AnsiColor::Yellow)))
-                    .write_to(print_stream)
-                    .unwrap(),
-            }
+    pub fn file_id(&self) -> FileId {
+        self.file_id
+    }
 
-            write!(
-                print_stream,
-                "[{}] {}{}\n",
-                report.tag,
-                Reset.render(),
-                report.message
-            )
-            .unwrap();
-
-            self.write_source_location(
-                print_stream,
-                max_number_of_digit,
-                &report.common_span.start_position,
-            );
-
-            for label in &report.labels {
-                if label.is_multiline() {
-                    occupied_multiline_labels.insert(label.clone(), false);
-                }
-            }
+    /// Prints every report to `print_stream` through the builder's emitter.
+    /// Since an arbitrary writer can't be checked for a terminal,
+    /// [`ColorMode::Auto`] treats it as a pipe; use
+    /// [`FileReportBuilder::print_terminal`] for stdout and stderr.
+    pub fn print(&mut self, print_stream: &mut dyn Write) -> io::Result<()> {
+        self.emit(print_stream, false)
+    }
 
-            let mut previous_line_rendered = true;
-            let mut render_source = false;
-            let mut current_dominant_label: Option<Label> = None;
-            for line in &segment {
-                let mut line_builder = line.chars.clone();
-                let mut applied_labels: Vec<Option<Label>> = Vec::new();
-                let (mut inserted_length, mut most_last_position) = (0, line.length + 1);
-                render_source = false;
-
-                for label in &report.labels {
-                    if label.is_in(line.line_number) {
-                        if !label.is_multiline() {
-                            if label.format.is_some() {
-                                let mut original_string_pos = label.span.start_position.column;
-                                let color_code = self.generate_color_code();
-                                self.insert_str(
-                                    &mut line_builder,
-                                    inserted_length + original_string_pos,
-                                    color_code.render_fg().to_string().as_str(),
-                                );
-                                inserted_length += color_code.render_fg().to_string().len();
-                                original_string_pos += label.span.offset();
-                                self.insert_str(
-                                    &mut line_builder,
-                                    inserted_length + original_string_pos,
-                                    Reset.render().to_string().as_str(),
-                                );
-                                inserted_length += Reset.render().to_string().len();
-
-                                if current_dominant_label.is_some()
-                                    && current_dominant_label.clone().unwrap().format.is_some()
-                                {
-                                    self.insert_str(
-                                        &mut line_builder,
-                                        inserted_length + original_string_pos,
-                                        self.generate_color_code().render_fg().to_string().as_str(),
-                                    );
-                                }
-                            }
-
-                            most_last_position =
-                                max(most_last_position, label.span.end_position.column + 2);
-                            applied_labels.push(Some(label.clone()));
-                        } else {
-                            if label.format.is_some() {
-                                let color_code = self.generate_color_code();
-                                let start_pos =
-                                    if label.span.start_position.line == line.line_number {
-                                        label.span.start_position.column
-                                    } else {
-                                        0
-                                    };
-                                self.insert_str(
-                                    &mut line_builder,
-                                    inserted_length + start_pos,
-                                    color_code.render_fg().to_string().as_str(),
-                                );
-                                inserted_length += color_code.render_fg().to_string().len();
-                                self.insert_str(
-                                    &mut line_builder,
-                                    inserted_length + (line.chars.len() - start_pos),
-                                    Reset.render().to_string().as_str(),
-                                );
-                            }
-
-                            occupied_multiline_labels.insert(label.clone(), true);
-                            current_dominant_label = Some(label.clone());
-                        }
-                    }
-
-                    if (label.span.start_position.line
-                        >= ((line.line_number as isize) - 1) as usize
-                        && label.span.start_position.line <= line.line_number + 1)
-                        || (label.span.end_position.line
-                            >= ((line.line_number as isize) - 1) as usize
-                            && label.span.end_position.line <= line.line_number + 1)
-                    {
-                        render_source = true;
-                    }
-                }
+    pub fn print_terminal<W: Write + IsTerminal>(
+        &mut self,
+        print_stream: &mut W,
+    ) -> io::Result<()> {
+        let is_terminal = print_stream.is_terminal();
+        self.emit(print_stream, is_terminal)
+    }
 
-                // dbg!(
-                //     render_source,
-                //     previous_line_rendered,
-                //     line.line_number,
-                //     line.chars.clone()
-                // );
-                if !render_source {
-                    if previous_line_rendered {
-                        Style::new()
-                            .fg_color(Some(Color::Ansi(AnsiColor::BrightBlack)))
-                            .write_to(print_stream)
-                            .unwrap();
-                        write!(
-                            print_stream,
-                            "{} {} {}",
-                            " ".repeat(max_number_of_digit),
-                            self.character_set.vertical_ellipsis,
-                            Reset.render(),
-                        )
-                        .unwrap();
-
-                        self.write_multi_line_label(
-                            print_stream,
-                            usize::MAX,
-                            &occupied_multiline_labels,
-                            None,
-                            self.character_set.vertical_ellipsis,
-                        );
-                        write!(print_stream, "\n").unwrap();
-                        previous_line_rendered = false;
-                    }
-                    continue;
-                }
+    /// Renders every report through the builder's emitter into a string,
+    /// with colors decided as for [`FileReportBuilder::print`].
+    pub fn render_to_string(&self) -> io::Result<String> {
+        let mut buffer = Vec::new();
+        self.emit(&mut buffer, false)?;
+        Ok(String::from_utf8_lossy(&buffer).to_string())
+    }
 
-                previous_line_rendered = true;
-
-                self.write_line_number(print_stream, line.line_number, max_number_of_digit, false);
-                let ended_label = self.write_multi_line_label(
-                    print_stream,
-                    line.line_number,
-                    &occupied_multiline_labels,
-                    None,
-                    self.character_set.vertical_bar,
-                );
-
-                write!(print_stream, "{}", line_builder).unwrap();
-
-                inserted_length = 0;
-                if !applied_labels.is_empty() {
-                    self.write_line_number(print_stream, usize::MAX, max_number_of_digit, true);
-                    self.write_multi_line_label(
-                        print_stream,
-                        usize::MAX,
-                        &occupied_multiline_labels,
-                        None,
-                        self.character_set.vertical_bar,
-                    );
-
-                    for label in &applied_labels {
-                        let label = match label {
-                            Some(label) => label,
-                            None => continue,
-                        };
-                        let space_length = label.span.start_position.column - inserted_length;
-                        if space_length > 0 {
-                            write!(print_stream, "{}", " ".repeat(space_length)).unwrap();
-                        }
-
-                        let offset = label.span.offset();
-                        let mut underline_builder = offset.to_string();
-                        for k in 0..offset {
-                            if offset / 2 == k {
-                                underline_builder.push(self.character_set.under_bar);
-                            } else {
-                                underline_builder.push(self.character_set.underline);
-                            }
-                        }
-
-                        Style::new()
-                            .fg_color(label.format)
-                            .write_to(print_stream)
-                            .unwrap();
-                        write!(print_stream, "{}{}", underline_builder, Reset.render()).unwrap();
-                        inserted_length += space_length + offset;
-                    }
-
-                    write!(print_stream, "\n").unwrap();
-
-                    for j in 1..=applied_labels.len() * 2 {
-                        self.write_line_number(print_stream, usize::MAX, max_number_of_digit, true);
-                        self.write_multi_line_label(
-                            print_stream,
-                            usize::MAX,
-                            &occupied_multiline_labels,
-                            None,
-                            self.character_set.vertical_bar,
-                        );
-
-                        inserted_length = 0;
-                        for k in 0..=applied_labels.len() {
-                            let tmp = applied_labels.clone();
-                            let label = match tmp.get(k) {
-                                Some(label) => match label {
-                                    Some(label) => label,
-                                    None => continue,
-                                },
-                                None => continue,
-                            };
-
-                            let space_length = label.span.start_position.column - inserted_length;
-                            let offset = label.span.offset() / 2;
-
-                            write!(print_stream, "{}", " ".repeat(space_length + offset)).unwrap();
-                            inserted_length += space_length + offset + 1;
-
-                            if j % 2 == 1 {
-                                applied_labels[k] = None;
-                                Style::new()
-                                    .fg_color(label.format)
-                                    .write_to(print_stream)
-                                    .unwrap();
-                                write!(
-                                    print_stream,
-                                    "{}{}{} {}",
-                                    self.character_set.left_bottom,
-                                    self.character_set
-                                        .horizontal_bar
-                                        .to_string()
-                                        .repeat(most_last_position - inserted_length),
-                                    Reset.render(),
-                                    label.message
-                                )
-                                .unwrap();
-
-                                if label.hint.is_some() {
-                                    writeln!(print_stream, "").unwrap();
-
-                                    self.write_line_number(
-                                        print_stream,
-                                        usize::MAX,
-                                        max_number_of_digit,
-                                        true,
-                                    );
-                                    self.write_multi_line_label(
-                                        print_stream,
-                                        usize::MAX,
-                                        &occupied_multiline_labels,
-                                        None,
-                                        self.character_set.vertical_bar,
-                                    );
-
-                                    write!(
-                                        print_stream,
-                                        "{}",
-                                        " ".repeat(
-                                            space_length + offset + most_last_position
-                                                - inserted_length
-                                                + 1
-                                        )
-                                    )
-                                    .unwrap();
-
-                                    Style::new()
-                                        .fg_color(Some(Color::Ansi(AnsiColor::BrightBlue)))
-                                        .write_to(print_stream)
-                                        .unwrap();
-                                    write!(
-                                        print_stream,
-                                        "!hint: {}{}",
-                                        label.hint.clone().unwrap(),
-                                        Reset.render()
-                                    )
-                                    .unwrap();
-                                }
-
-                                break;
-                            }
-
-                            Style::new()
-                                .fg_color(label.format)
-                                .write_to(print_stream)
-                                .unwrap();
-                            write!(
-                                print_stream,
-                                "{}{}",
-                                self.character_set.vertical_bar,
-                                Reset.render(),
-                            )
-                            .unwrap();
-                        }
-
-                        writeln!(print_stream, "").unwrap();
-                    }
-                }
+    /// Hands every report to `sink`, which counts, deduplicates and emits
+    /// them alongside reports from other files. Returns how many were
+    /// accepted.
+    pub fn send(&self, sink: &DiagnosticSink) -> usize {
+        self.reports
+            .iter()
+            .filter(|report| sink.report(self.file_id, (*report).clone()))
+            .count()
+    }
 
-                if ended_label.is_some() {
-                    self.write_line_number(print_stream, usize::MAX, max_number_of_digit, true);
-                    self.write_multi_line_label(
-                        print_stream,
-                        usize::MAX,
-                        &occupied_multiline_labels,
-                        None,
-                        self.character_set.vertical_bar,
-                    );
-                    writeln!(print_stream, "").unwrap();
-                    self.write_line_number(print_stream, usize::MAX, max_number_of_digit, true);
-                    self.write_multi_line_label(
-                        print_stream,
-                        usize::MAX,
-                        &occupied_multiline_labels,
-                        ended_label.clone(),
-                        self.character_set.vertical_bar,
-                    );
-                    occupied_multiline_labels.insert(ended_label.clone().unwrap(), false);
-                    Style::new()
-                        .fg_color(ended_label.clone().unwrap().format)
-                        .write_to(print_stream)
-                        .unwrap();
-                    write!(
-                        print_stream,
-                        "{}{} {}",
-                        self.character_set
-                            .horizontal_bar
-                            .to_string()
-                            .repeat(most_last_position),
-                        Reset.render(),
-                        ended_label.clone().unwrap().message
-                    )
-                    .unwrap();
-
-                    if ended_label.clone().unwrap().hint.is_some() {
-                        writeln!(print_stream, "").unwrap();
-
-                        self.write_line_number(print_stream, usize::MAX, max_number_of_digit, true);
-                        self.write_multi_line_label(
-                            print_stream,
-                            usize::MAX,
-                            &occupied_multiline_labels,
-                            None,
-                            self.character_set.vertical_bar,
-                        );
-
-                        write!(print_stream, "{}", " ".repeat(most_last_position)).unwrap();
-
-                        Style::new()
-                            .fg_color(Some(Color::Ansi(AnsiColor::BrightBlue)))
-                            .write_to(print_stream)
-                            .unwrap();
-                        write!(
-                            print_stream,
-                            "!hint: {}{}",
-                            ended_label.clone().unwrap().hint.clone().unwrap(),
-                            Reset.render()
-                        )
-                        .unwrap();
-                    }
-
-                    writeln!(print_stream, "").unwrap();
-                }
-            }
+    fn emit(&self, print_stream: &mut dyn Write, is_terminal: bool) -> io::Result<()> {
+        let emitter = match &self.emitter {
+            Some(emitter) => emitter.clone(),
+            None => self.error_format.emitter(self.human_emitter()),
+        };
 
-            Style::new()
-                .fg_color(Some(Color::Ansi(AnsiColor::BrightBlack)))
-                .write_to(print_stream)
-                .unwrap();
-            writeln!(
-                print_stream,
-                "{}{}{}",
-                self.character_set
-                    .horizontal_bar
-                    .to_string()
-                    .repeat(max_number_of_digit + 1),
-                self.character_set.right_bottom,
-                Reset.render()
-            )
-            .unwrap();
-            print_stream.flush().unwrap();
-        }
+        self.emit_with(emitter.as_ref(), print_stream, is_terminal)
     }
 
-    fn write_line_number(
-        &mut self,
-        print_stream: &mut dyn std::io::Write,
-        line_number: usize,
-        max_line_digit: usize,
-        is_virtual_line: bool,
-    ) {
-        Style::new()
-            .fg_color(Some(Color::Ansi(AnsiColor::BrightBlack)))
-            .write_to(print_stream)
-            .unwrap();
-        if is_virtual_line {
-            write!(
-                print_stream,
-                "{:width$} {} ",
-                "",
-                self.character_set.vertical_bar_breaking,
-                width = max_line_digit
-            )
-            .unwrap();
-        } else {
-            write!(
-                print_stream,
-                "{:width$} {} ",
-                line_number,
-                self.character_set.vertical_bar,
-                width = max_line_digit
-            )
-            .unwrap();
-        }
-        write!(print_stream, "{}", Reset.render()).unwrap();
+    fn emit_with(
+        &self,
+        emitter: &dyn Emitter,
+        print_stream: &mut dyn Write,
+        is_terminal: bool,
+    ) -> io::Result<()> {
+        emitter.emit(
+            print_stream,
+            &self.source_map,
+            &self.resolve_reports(),
+            self.color_mode.is_enabled(is_terminal),
+        )
     }
 
-    fn write_multi_line_label(
+    /// Prints every report as one JSON object per line. With
+    /// `include_rendered`, each object carries the human-readable output in
+    /// its `rendered` field.
+    pub fn print_json(
         &mut self,
-        print_stream: &mut dyn std::io::Write,
-        line_number: usize,
-        label_map: &HashMap<Label, bool>,
-        terminated_label: Option<Label>,
-        vertical_bar_variant: char,
-    ) -> Option<Label> {
-        let mut entries = Vec::new();
-        for (label, is_occupied) in label_map {
-            entries.push((label.clone(), is_occupied.clone()));
-        }
-        let mut should_print = true;
-        let mut last_index = 0;
-        let mut ended_label = None;
-
-        for (i, label) in entries.iter().enumerate() {
-            if label.1 {
-                Style::new()
-                    .fg_color(label.0.format)
-                    .write_to(print_stream)
-                    .unwrap();
-
-                if label.0.span.start_position.line == line_number {
-                    write!(print_stream, "{}", self.character_set.left_top).unwrap();
-                    write!(
-                        print_stream,
-                        "{}",
-                        self.character_set
-                            .horizontal_bar
-                            .to_string()
-                            .repeat((entries.len() - i) * 2)
-                    )
-                    .unwrap();
-                    write!(print_stream, "{}", self.character_set.right_arrow).unwrap();
-                    should_print = false;
-                    break;
-                } else if label.0.span.end_position.line == line_number {
-                    write!(print_stream, "{}", self.character_set.left_cross).unwrap();
-                    write!(
-                        print_stream,
-                        "{}",
-                        self.character_set
-                            .horizontal_bar
-                            .to_string()
-                            .repeat((entries.len() - i) * 2)
-                    )
-                    .unwrap();
-                    write!(print_stream, "{}", self.character_set.right_arrow).unwrap();
-                    should_print = false;
-                    ended_label = Some(label.0.clone());
-                    break;
-                } else if terminated_label.is_some()
-                    && label.0 == *terminated_label.as_ref().unwrap()
-                {
-                    write!(
-                        print_stream,
-                        "{}{}",
-                        self.character_set.left_bottom,
-                        self.character_set
-                            .horizontal_bar
-                            .to_string()
-                            .repeat((entries.len() - i) * 2 + 2)
-                    )
-                    .unwrap();
-                    return None;
-                } else {
-                    write!(print_stream, "{} ", vertical_bar_variant).unwrap();
-                }
+        print_stream: &mut dyn Write,
+        include_rendered: bool,
+    ) -> io::Result<()> {
+        let emitter = JsonEmitter::new(include_rendered.then(|| self.human_emitter()));
+        self.emit_with(&emitter, print_stream, false)
+    }
 
-                write!(print_stream, "{}", Reset.render()).unwrap();
-            } else {
-                write!(print_stream, "  ").unwrap();
-            }
+    /// Prints every report as `file:line:col: error[E01]: message` lines.
+    pub fn print_short(&mut self, print_stream: &mut dyn Write) -> io::Result<()> {
+        let emitter = ShortEmitter::new(self.theme.clone());
+        self.emit_with(&emitter, print_stream, false)
+    }
 
-            last_index = i + 1;
-        }
+    pub fn print_sarif(&mut self, print_stream: &mut dyn Write) -> io::Result<()> {
+        self.emit_with(&SarifEmitter, print_stream, false)
+    }
 
-        if should_print {
-            let count = (entries.len() - last_index) * 2 + 3;
-            write!(print_stream, "{:width$}", "", width = count).unwrap();
-        } else {
-            write!(print_stream, " ").unwrap();
-        }
+    /// Prints every report as a GitHub Actions workflow command, so it shows
+    /// up as an annotation on the pull request.
+    pub fn print_github(&mut self, print_stream: &mut dyn Write) -> io::Result<()> {
+        self.emit_with(&GithubEmitter, print_stream, false)
+    }
+
+    /// Prints the reports as HTML, either as bare `<pre>` blocks or as a
+    /// standalone page with the stylesheet embedded.
+    pub fn print_html(&mut self, print_stream: &mut dyn Write, full_page: bool) -> io::Result<()> {
+        let emitter = HtmlEmitter::new(self.human_emitter(), full_page);
+        self.emit_with(&emitter, print_stream, false)
+    }
 
-        ended_label
+    pub fn human_emitter(&self) -> HumanEmitter {
+        let mut human_emitter = HumanEmitter::new(self.theme.character_set, self.tab_width)
+            .theme(self.theme.clone())
+            .context_lines(self.context_lines.0, self.context_lines.1)
+            .full_span_lines(self.full_span_lines);
+        human_emitter.max_width = self.max_width;
+        human_emitter
     }
 
-    fn generate_color_code(&self) -> AnsiColor {
-        let colors = vec![
-            AnsiColor::BrightRed,
-            AnsiColor::BrightGreen,
-            AnsiColor::BrightYellow,
-            AnsiColor::BrightBlue,
-            AnsiColor::BrightMagenta,
-            AnsiColor::BrightCyan,
-            AnsiColor::BrightWhite,
-        ];
-        let mut rng = rand::thread_rng();
-        colors[rng.gen_range(0..colors.len())]
+    fn resolve_reports(&self) -> Vec<Report> {
+        self.reports
+            .iter()
+            .map(|report| {
+                let mut report = report.clone();
+                report.resolve_spans(&self.source_map, self.file_id);
+                report.assign_colors(&self.theme.label_palette);
+                report.sort_labels();
+                report
+            })
+            .collect()
     }
 
-    fn write_source_location(
+    /// Starts a report for a registered code, with the code's severity.
+    pub fn report(
         &mut self,
-        print_stream: &mut dyn std::io::Write,
-        max_line_digit: usize,
-        start_position: &Position,
-    ) {
-        Style::new()
-            .fg_color(Some(Color::Ansi(AnsiColor::BrightBlack)))
-            .write_to(print_stream)
-            .unwrap();
-        write!(
-            print_stream,
-            "{:>width$}{}[",
-            self.character_set.left_top,
-            self.character_set.horizontal_bar,
-            width = max_line_digit + 2
-        )
-        .unwrap();
-        write!(
-            print_stream,
-            "{}{}:{}:{}",
-            Reset.render(),
-            self.source_name,
-            start_position.line,
-            start_position.column
-        )
-        .unwrap();
-        Style::new()
-            .fg_color(Some(Color::Ansi(AnsiColor::BrightBlack)))
-            .write_to(print_stream)
-            .unwrap();
-        writeln!(print_stream, "]{}", Reset.render()).unwrap();
-    }
-
-    fn insert_str(&self, string: &mut String, index: usize, value: &str) {
-        let mut new_string = String::new();
-        for (i, c) in string.chars().enumerate() {
-            if i == index {
-                new_string.push_str(value);
-            }
-            new_string.push(c);
-        }
-        *string = new_string;
+        code: &'static ErrorCode,
+        span: Span,
+        message: String,
+    ) -> ReportBuilder {
+        ReportBuilder::new(self.clone(), Report::from_code(code, span, message))
     }
 
     pub fn error(&mut self, span: Span, message: String) -> ReportBuilder {
@@ -686,20 +237,148 @@ impl FileReportBuilder {
         ReportBuilder::new(self.clone(), Report::warning(span, message))
     }
 
+    pub fn note(&mut self, span: Span, message: String) -> ReportBuilder {
+        ReportBuilder::new(self.clone(), Report::note(span, message))
+    }
+
+    pub fn help(&mut self, span: Span, message: String) -> ReportBuilder {
+        ReportBuilder::new(self.clone(), Report::help(span, message))
+    }
+
+    pub fn info(&mut self, span: Span, message: String) -> ReportBuilder {
+        ReportBuilder::new(self.clone(), Report::info(span, message))
+    }
+
+    pub fn bug(&mut self, span: Span, message: String) -> ReportBuilder {
+        ReportBuilder::new(self.clone(), Report::bug(span, message))
+    }
+
+    /// Returns the source of `file_id` with every machine-applicable
+    /// suggestion into it applied, along with how many were applied.
+    pub fn fixed_source(&self, file_id: FileId) -> Option<(String, usize)> {
+        let source = self.source_map.get(file_id)?;
+        let reports = self.resolve_reports();
+        let suggestions: Vec<&Suggestion> = reports
+            .iter()
+            .flat_map(|report| report.flatten())
+            .flat_map(|report| &report.labels)
+            .filter(|label| label.file_id == Some(file_id))
+            .flat_map(|label| &label.suggestions)
+            .collect();
+
+        Some(apply_suggestions(&source, &suggestions))
+    }
+
+    /// Writes the machine-applicable suggestions back to every source file
+    /// they point into, returning how many were applied. In-memory sources
+    /// and files that were not valid UTF-8 are left untouched.
+    pub fn apply_fixes(&self) -> std::io::Result<usize> {
+        let mut files: Vec<FileId> = self
+            .resolve_reports()
+            .iter()
+            .flat_map(|report| report.flatten())
+            .flat_map(|report| report.files())
+            .collect();
+        files.sort();
+        files.dedup();
+
+        let mut applied = 0;
+        for file_id in files {
+            let path = match self.source_map.path(file_id) {
+                Some(path) => path,
+                None => continue,
+            };
+
+            // A lossily loaded file would lose its invalid bytes if rewritten.
+            let source = match self.source_map.get(file_id) {
+                Some(source) if !source.is_lossy() => source,
+                _ => continue,
+            };
+
+            if let Some((fixed, count)) = self.fixed_source(file_id) {
+                if count > 0 {
+                    let fixed = match source.has_bom {
+                        true => format!("{}{}", BOM, fixed),
+                        false => fixed,
+                    };
+                    std::fs::write(path, fixed)?;
+                    applied += count;
+                }
+            }
+        }
+
+        Ok(applied)
+    }
+
     pub fn source_name(&mut self, source_name: String) -> FileReportBuilder {
-        self.source_name = source_name;
+        self.source_map.set_name(self.file_id, source_name);
         self.clone()
     }
 
     pub fn character_set(&mut self, character_set: CharacterSet) -> FileReportBuilder {
-        self.character_set = character_set;
+        self.theme.character_set = character_set;
+        self.clone()
+    }
+
+    pub fn theme(&mut self, theme: Theme) -> FileReportBuilder {
+        self.theme = theme;
+        self.clone()
+    }
+
+    pub fn color_mode(&mut self, color_mode: ColorMode) -> FileReportBuilder {
+        self.color_mode = color_mode;
+        self.clone()
+    }
+
+    pub fn error_format(&mut self, error_format: ErrorFormat) -> FileReportBuilder {
+        self.error_format = error_format;
+        self.emitter = None;
+        self.clone()
+    }
+
+    /// Replaces the emitter chosen by the error format with a custom one.
+    pub fn emitter(&mut self, emitter: Arc<dyn Emitter>) -> FileReportBuilder {
+        self.emitter = Some(emitter);
+        self.clone()
+    }
+
+    pub fn tab_width(&mut self, tab_width: usize) -> FileReportBuilder {
+        self.tab_width = tab_width;
+        self.clone()
+    }
+
+    /// Sets how many lines of source are shown before and after each line a
+    /// label starts or ends on.
+    pub fn context_lines(&mut self, before: usize, after: usize) -> FileReportBuilder {
+        self.context_lines = (before, after);
+        self.clone()
+    }
+
+    /// Shows snippets spanning at most `full_span_lines` lines in full
+    /// instead of folding the lines between labels.
+    pub fn full_span_lines(&mut self, full_span_lines: usize) -> FileReportBuilder {
+        self.full_span_lines = full_span_lines;
+        self.clone()
+    }
+
+    /// Cuts source lines wider than `max_width` cells down to a window around
+    /// their labels.
+    pub fn max_width(&mut self, max_width: usize) -> FileReportBuilder {
+        self.max_width = Some(max_width);
         self.clone()
     }
 }
 
+impl Display for FileReportBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render_to_string().map_err(|_| fmt::Error)?)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ReportBuilder {
     parent_builder: FileReportBuilder,
+    parent_report: Option<Box<ReportBuilder>>,
     report: Report,
 }
 
@@ -707,6 +386,7 @@ impl ReportBuilder {
     pub fn new(parent_builder: FileReportBuilder, report: Report) -> Self {
         Self {
             parent_builder,
+            parent_report: None,
             report,
         }
     }
@@ -716,11 +396,66 @@ impl ReportBuilder {
         self.clone()
     }
 
+    pub fn code(&mut self, code: &'static ErrorCode) -> Self {
+        self.report.tag = code.code.to_string();
+        self.report.code = Some(code);
+        self.clone()
+    }
+
+    /// Points the report, and the labels that don't name a file of their
+    /// own, into another file than the builder's, as a child pointing at an
+    /// earlier definition does.
+    pub fn file(&mut self, file_id: FileId) -> Self {
+        self.report.file_id = Some(file_id);
+        self.clone()
+    }
+
     pub fn label(&mut self, span: Span, message: String) -> LabelBuilder {
         LabelBuilder::new(self, Label::new(span, message))
     }
 
+    /// Adds a `= note: ...` footer below the report's snippets.
+    pub fn note(&mut self, message: String) -> Self {
+        self.report
+            .add_footer(Footer::new(ReportType::Note, message));
+        self.clone()
+    }
+
+    /// Adds a `= help: ...` footer below the report's snippets.
+    pub fn help(&mut self, message: String) -> Self {
+        self.report
+            .add_footer(Footer::new(ReportType::Help, message));
+        self.clone()
+    }
+
+    /// Starts a child report, rendered nested under this one with a snippet
+    /// of its own. Finish it with [`ReportBuilder::build_child`] to go back
+    /// to this report, or with [`ReportBuilder::build`] to finish both.
+    pub fn child(&mut self, report_type: ReportType, span: Span, message: String) -> ReportBuilder {
+        ReportBuilder {
+            parent_builder: self.parent_builder.clone(),
+            parent_report: Some(Box::new(self.clone())),
+            report: Report::with_type(report_type, span, message),
+        }
+    }
+
+    /// Attaches a child report to its parent and returns the parent. A
+    /// top-level report is returned unchanged.
+    pub fn build_child(&mut self) -> ReportBuilder {
+        match self.parent_report.take() {
+            Some(mut parent_report) => {
+                parent_report.report.add_child(self.report.clone());
+                *parent_report
+            }
+            None => self.clone(),
+        }
+    }
+
     pub fn build(&mut self) -> FileReportBuilder {
+        if self.parent_report.is_some() {
+            return self.build_child().build();
+        }
+
         self.parent_builder.reports.push(self.report.clone());
         self.parent_builder.clone()
     }
@@ -745,11 +480,29 @@ impl LabelBuilder {
         self.clone()
     }
 
+    /// Points the label into another file than its report's.
+    pub fn file(&mut self, file_id: FileId) -> Self {
+        self.label.file_id = Some(file_id);
+        self.clone()
+    }
+
     pub fn hint(&mut self, hint: String) -> Self {
         self.label.hint = Some(hint);
         self.clone()
     }
 
+    pub fn suggestion(
+        &mut self,
+        span: Span,
+        replacement: String,
+        applicability: Applicability,
+    ) -> Self {
+        self.label
+            .suggestions
+            .push(Suggestion::new(span, replacement, applicability));
+        self.clone()
+    }
+
     pub fn build(&mut self) -> ReportBuilder {
         self.parent_builder.report.labels.push(self.label.clone());
         self.parent_builder.clone()
@@ -0,0 +1,360 @@
+use super::{
+    codes,
+    emitter::Emitter,
+    label::Label,
+    report::{Report, ReportType},
+    source_map::{file_name, SourceMap},
+    span::Span,
+    suggestion::Suggestion,
+};
+use serde::Serialize;
+use std::io::{self, Write};
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// A SARIF 2.1.0 log holding a single run of the compiler.
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub column_kind: &'static str,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub information_uri: &'static str,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_description: Option<SarifMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<SarifMultiformatMessage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifMultiformatMessage {
+    pub text: String,
+    pub markdown: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub level: &'static str,
+    pub message: SarifMessage,
+    pub locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related_locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<SarifFix>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifFix {
+    pub description: SarifMessage,
+    pub artifact_changes: Vec<SarifArtifactChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifArtifactChange {
+    pub artifact_location: SarifArtifactLocation,
+    pub replacements: Vec<SarifReplacement>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifReplacement {
+    pub deleted_region: SarifRegion,
+    pub inserted_content: SarifArtifactContent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifArtifactContent {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    pub physical_location: SarifPhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<SarifMessage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: SarifArtifactLocation,
+    pub region: SarifRegion,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SarifLog {
+    /// Builds a log from resolved reports, naming files through `source_map`.
+    pub fn new(reports: &[Report], source_map: &SourceMap) -> Self {
+        let mut rules: Vec<SarifRule> = Vec::new();
+        for report in reports {
+            if !rules.iter().any(|rule| rule.id == report.tag) {
+                rules.push(SarifRule::new(&report.tag));
+            }
+        }
+
+        Self {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: env!("CARGO_PKG_NAME"),
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: "https://github.com/icxd/yakou",
+                        rules,
+                    },
+                },
+                column_kind: "unicodeCodePoints",
+                results: reports
+                    .iter()
+                    .map(|report| SarifResult::new(report, source_map))
+                    .collect(),
+            }],
+        }
+    }
+}
+
+/// Writes all reports as one pretty-printed SARIF log.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SarifEmitter;
+
+impl Emitter for SarifEmitter {
+    fn emit(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        reports: &[Report],
+        _colored: bool,
+    ) -> io::Result<()> {
+        let log = SarifLog::new(reports, source_map);
+
+        writeln!(print_stream, "{}", serde_json::to_string_pretty(&log)?)
+    }
+}
+
+impl SarifRule {
+    /// Creates a rule, describing it from the code registry if `id` is a
+    /// registered code.
+    pub fn new(id: &str) -> Self {
+        let code = codes::lookup(id);
+
+        Self {
+            id: id.to_string(),
+            short_description: code.map(|code| SarifMessage {
+                text: code.summary.to_string(),
+            }),
+            help: code.map(|code| SarifMultiformatMessage {
+                text: code.explanation.to_string(),
+                markdown: code.explanation.to_string(),
+            }),
+        }
+    }
+}
+
+impl SarifResult {
+    pub fn new(report: &Report, source_map: &SourceMap) -> Self {
+        Self {
+            rule_id: report.tag.clone(),
+            level: sarif_level(report.report_type),
+            message: SarifMessage {
+                text: report
+                    .footers
+                    .iter()
+                    .fold(report.message.clone(), |text, footer| {
+                        format!(
+                            "{}\n{}: {}",
+                            text,
+                            footer.report_type.name(),
+                            footer.message
+                        )
+                    }),
+            },
+            locations: vec![SarifLocation::new(
+                None,
+                &report.common_span,
+                &file_name(source_map, report.file_id),
+                None,
+            )],
+            related_locations: SarifLocation::related(report, source_map),
+            fixes: report
+                .flatten()
+                .into_iter()
+                .flat_map(|report| &report.labels)
+                .flat_map(|label| {
+                    let file_name = file_name(source_map, label.file_id);
+                    label
+                        .suggestions
+                        .iter()
+                        .map(move |suggestion| SarifFix::new(label, suggestion, &file_name))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl SarifFix {
+    pub fn new(label: &Label, suggestion: &Suggestion, file_name: &str) -> Self {
+        Self {
+            description: SarifMessage {
+                text: format!(
+                    "{} ({})",
+                    label.hint.as_ref().unwrap_or(&label.message),
+                    suggestion.applicability.name()
+                ),
+            },
+            artifact_changes: vec![SarifArtifactChange {
+                artifact_location: SarifArtifactLocation::new(file_name),
+                replacements: vec![SarifReplacement {
+                    deleted_region: SarifRegion::new(&suggestion.span),
+                    inserted_content: SarifArtifactContent {
+                        text: suggestion.replacement.clone(),
+                    },
+                }],
+            }],
+        }
+    }
+}
+
+impl SarifArtifactLocation {
+    pub fn new(file_name: &str) -> Self {
+        Self {
+            uri: file_name.replace('\\', "/"),
+        }
+    }
+}
+
+impl SarifLocation {
+    pub fn new(
+        id: Option<usize>,
+        span: &Span,
+        file_name: &str,
+        message: Option<SarifMessage>,
+    ) -> Self {
+        Self {
+            id,
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation::new(file_name),
+                region: SarifRegion::new(span),
+            },
+            message,
+        }
+    }
+
+    /// Collects the labels of a report and its children, with a location for
+    /// each child's own span, numbered in order.
+    pub fn related(report: &Report, source_map: &SourceMap) -> Vec<Self> {
+        let mut locations = Vec::new();
+
+        for (index, nested) in report.flatten().into_iter().enumerate() {
+            if index > 0 {
+                locations.push(Self::new(
+                    Some(locations.len()),
+                    &nested.common_span,
+                    &file_name(source_map, nested.file_id),
+                    Some(SarifMessage {
+                        text: format!("{}: {}", nested.report_type.name(), nested.message),
+                    }),
+                ));
+            }
+
+            for label in &nested.labels {
+                locations.push(Self::from_label(
+                    locations.len(),
+                    label,
+                    &file_name(source_map, label.file_id),
+                ));
+            }
+        }
+
+        locations
+    }
+
+    pub fn from_label(id: usize, label: &Label, file_name: &str) -> Self {
+        let text = match &label.hint {
+            Some(hint) => format!("{} (hint: {})", label.message, hint),
+            None => label.message.clone(),
+        };
+
+        Self::new(
+            Some(id),
+            &label.span,
+            file_name,
+            Some(SarifMessage { text }),
+        )
+    }
+}
+
+impl SarifRegion {
+    /// Describes a span by line and column only. Span offsets count from
+    /// after a stripped byte order mark and into lossily decoded text, so
+    /// they are not byte offsets into the artifact.
+    pub fn new(span: &Span) -> Self {
+        Self {
+            start_line: span.start_position.line.max(1),
+            start_column: span.start_position.column + 1,
+            end_line: span.end_position.line.max(1),
+            end_column: span.end_position.column + 1,
+        }
+    }
+}
+
+pub fn sarif_level(report_type: ReportType) -> &'static str {
+    match report_type {
+        ReportType::Error | ReportType::Bug => "error",
+        ReportType::Warning => "warning",
+        ReportType::Note | ReportType::Help | ReportType::Info => "note",
+    }
+}
@@ -0,0 +1,155 @@
+use super::{
+    emitter::Emitter,
+    report::{Report, ReportType},
+    source_map::{file_name, SourceMap},
+    span::Span,
+    theme::Theme,
+};
+use anstyle::Style;
+use std::io::{self, Write};
+
+/// Writes each report as `file:line:col: error[E01]: message`, followed by a
+/// `note` line for every label, the way Vim's quickfix list and Emacs'
+/// compilation mode expect. Columns are 1-based.
+#[derive(Debug, Clone, Default)]
+pub struct ShortEmitter {
+    pub theme: Theme,
+}
+
+impl ShortEmitter {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+
+    pub fn render_report(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        report: &Report,
+        colored: bool,
+    ) -> io::Result<()> {
+        let location = location(source_map, report, &report.common_span);
+        let header = match report.tag == report.report_type.default_tag() {
+            true => report.report_type.name().to_string(),
+            false => format!("{}[{}]", report.report_type.name(), report.tag),
+        };
+        self.write_line(
+            print_stream,
+            &location,
+            report.report_type,
+            &header,
+            &report.message,
+            colored,
+        )?;
+
+        for label in &report.labels {
+            let location = match label.file_id {
+                Some(file_id) => format!(
+                    "{}:{}",
+                    source_map.display_name(file_id),
+                    position(&label.span)
+                ),
+                None => location.clone(),
+            };
+            self.write_line(
+                print_stream,
+                &location,
+                ReportType::Note,
+                ReportType::Note.name(),
+                &label.message,
+                colored,
+            )?;
+
+            if let Some(hint) = &label.hint {
+                self.write_line(
+                    print_stream,
+                    &location,
+                    ReportType::Help,
+                    ReportType::Help.name(),
+                    hint,
+                    colored,
+                )?;
+            }
+        }
+
+        for footer in &report.footers {
+            self.write_line(
+                print_stream,
+                &location,
+                footer.report_type,
+                footer.report_type.name(),
+                &footer.message,
+                colored,
+            )?;
+        }
+
+        for child in &report.children {
+            self.render_report(print_stream, source_map, child, colored)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes one line, folding multi-line messages onto it so every line of
+    /// output stays a complete `file:line:col:` entry.
+    fn write_line(
+        &self,
+        print_stream: &mut dyn Write,
+        location: &str,
+        report_type: ReportType,
+        header: &str,
+        message: &str,
+        colored: bool,
+    ) -> io::Result<()> {
+        let style = match colored {
+            true => self.theme.header_style(report_type),
+            false => Style::new(),
+        };
+
+        writeln!(
+            print_stream,
+            "{}: {}{}{:#}: {}",
+            location,
+            style,
+            header,
+            style,
+            message.lines().collect::<Vec<_>>().join(" ")
+        )
+    }
+}
+
+impl Emitter for ShortEmitter {
+    fn emit(
+        &self,
+        print_stream: &mut dyn Write,
+        source_map: &SourceMap,
+        reports: &[Report],
+        colored: bool,
+    ) -> io::Result<()> {
+        for report in reports {
+            self.render_report(print_stream, source_map, report, colored)?;
+        }
+
+        print_stream.flush()
+    }
+
+    fn active_theme(&self) -> Option<&Theme> {
+        Some(&self.theme)
+    }
+}
+
+fn location(source_map: &SourceMap, report: &Report, span: &Span) -> String {
+    format!(
+        "{}:{}",
+        file_name(source_map, report.file_id),
+        position(span)
+    )
+}
+
+fn position(span: &Span) -> String {
+    format!(
+        "{}:{}",
+        span.start_position.line.max(1),
+        span.start_position.column + 1
+    )
+}
@@ -0,0 +1,322 @@
+use super::{
+    color_mode::ColorMode,
+    emitter::Emitter,
+    report::{Footer, Report, ReportType},
+    source_map::{FileId, SourceMap},
+    span::Span,
+    theme::Theme,
+};
+use std::{
+    collections::HashSet,
+    fmt::{self, Debug, Formatter},
+    io::{self, stderr, IsTerminal, Write},
+    sync::{Arc, Mutex},
+};
+
+/// The central handler compiler passes report into.
+///
+/// A sink is a cheap handle: clones share the same counts and queue, so each
+/// pass can hold its own. Reports are counted and deduplicated as they
+/// arrive and emitted together by [`DiagnosticSink::flush`], which keeps
+/// formats like SARIF a single document.
+#[derive(Clone)]
+pub struct DiagnosticSink {
+    source_map: Arc<SourceMap>,
+    emitter: Arc<dyn Emitter>,
+    is_terminal: bool,
+    state: Arc<Mutex<SinkState>>,
+}
+
+struct SinkState {
+    print_stream: Box<dyn Write + Send>,
+    color_mode: ColorMode,
+    max_errors: Option<usize>,
+    warnings_as_errors: bool,
+    pending: Vec<Report>,
+    seen: HashSet<Report>,
+    loaded_files: HashSet<FileId>,
+    error_count: usize,
+    warning_count: usize,
+    suppressed_count: usize,
+}
+
+impl DiagnosticSink {
+    pub fn new(
+        source_map: Arc<SourceMap>,
+        emitter: Arc<dyn Emitter>,
+        print_stream: Box<dyn Write + Send>,
+    ) -> Self {
+        Self::with_terminal(source_map, emitter, print_stream, false)
+    }
+
+    /// Creates a sink printing to stderr, with colors decided by whether
+    /// stderr is a terminal.
+    pub fn stderr(source_map: Arc<SourceMap>, emitter: Arc<dyn Emitter>) -> Self {
+        let is_terminal = stderr().is_terminal();
+        Self::with_terminal(source_map, emitter, Box::new(stderr()), is_terminal)
+    }
+
+    fn with_terminal(
+        source_map: Arc<SourceMap>,
+        emitter: Arc<dyn Emitter>,
+        print_stream: Box<dyn Write + Send>,
+        is_terminal: bool,
+    ) -> Self {
+        Self {
+            source_map,
+            emitter,
+            is_terminal,
+            state: Arc::new(Mutex::new(SinkState {
+                print_stream,
+                color_mode: ColorMode::default(),
+                max_errors: None,
+                warnings_as_errors: false,
+                pending: Vec::new(),
+                seen: HashSet::new(),
+                loaded_files: HashSet::new(),
+                error_count: 0,
+                warning_count: 0,
+                suppressed_count: 0,
+            })),
+        }
+    }
+
+    pub fn color_mode(&mut self, color_mode: ColorMode) -> DiagnosticSink {
+        self.state.lock().unwrap().color_mode = color_mode;
+        self.clone()
+    }
+
+    /// Stops accepting reports once `max_errors` errors have been reported.
+    /// [`DiagnosticSink::finish`] notes how many were suppressed after that.
+    pub fn max_errors(&mut self, max_errors: usize) -> DiagnosticSink {
+        self.state.lock().unwrap().max_errors = Some(max_errors);
+        self.clone()
+    }
+
+    /// Reports every warning as an error, as `-W error` does.
+    pub fn warnings_as_errors(&mut self, warnings_as_errors: bool) -> DiagnosticSink {
+        self.state.lock().unwrap().warnings_as_errors = warnings_as_errors;
+        self.clone()
+    }
+
+    /// Queues a report, resolving its spans against `file_id` unless it names
+    /// a file of its own. Returns `false` if the report was dropped, either as
+    /// an exact duplicate of an earlier one or because the error limit was
+    /// reached.
+    pub fn report(&self, file_id: FileId, mut report: Report) -> bool {
+        let mut state = self.state.lock().unwrap();
+        report.resolve_spans(&self.source_map, file_id);
+        for file_id in report.files() {
+            if state.loaded_files.insert(file_id) {
+                self.report_load_problems(&mut state, file_id);
+            }
+        }
+
+        self.queue(&mut state, report)
+    }
+
+    /// Counts and queues a resolved report, applying the error limit,
+    /// `-W error` and deduplication.
+    fn queue(&self, state: &mut SinkState, mut report: Report) -> bool {
+        if state.limit_reached() {
+            state.suppressed_count += 1;
+            return false;
+        }
+
+        report.assign_colors(&self.theme().label_palette);
+        report.sort_labels();
+
+        if state.warnings_as_errors && report.report_type == ReportType::Warning {
+            if report.tag == ReportType::Warning.default_tag() {
+                report.tag = ReportType::Error.default_tag().to_string();
+            }
+            report.report_type = ReportType::Error;
+            report.add_footer(Footer::new(
+                ReportType::Note,
+                "this warning was turned into an error by `-W error`".to_string(),
+            ));
+        }
+
+        if !state.seen.insert(report.clone()) {
+            return false;
+        }
+
+        match report.report_type {
+            ReportType::Error | ReportType::Bug => state.error_count += 1,
+            ReportType::Warning => state.warning_count += 1,
+            _ => {}
+        }
+        state.pending.push(report);
+        true
+    }
+
+    /// The emitter's theme, which picks label colors and styles the summary,
+    /// or the default one for emitters without colors.
+    fn theme(&self) -> Theme {
+        self.emitter.active_theme().cloned().unwrap_or_default()
+    }
+
+    /// Queues a warning for a file that could not be read, or was read
+    /// lossily, the first time a report points into it.
+    fn report_load_problems(&self, state: &mut SinkState, file_id: FileId) {
+        let name = self.source_map.display_name(file_id);
+        let mut reports: Vec<Report> = self
+            .source_map
+            .load_warnings(file_id)
+            .into_iter()
+            .map(|warning| {
+                Report::warning(
+                    Span::byte_range(warning.offset, warning.offset),
+                    format!("`{}`: {}", name, warning.message),
+                )
+            })
+            .collect();
+        if let Some(error) = self.source_map.load_error(file_id) {
+            reports.push(Report::warning(
+                Span::single_line(1, 0, 0),
+                format!("could not read `{}`: {}", name, error),
+            ));
+        }
+
+        for mut report in reports {
+            report.resolve_spans(&self.source_map, file_id);
+            self.queue(state, report);
+        }
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.state.lock().unwrap().error_count
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.state.lock().unwrap().warning_count
+    }
+
+    /// How many reports were dropped because the error limit was reached.
+    pub fn suppressed_count(&self) -> usize {
+        self.state.lock().unwrap().suppressed_count
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// Whether the error limit has been reached, after which passes should
+    /// stop as further reports are dropped.
+    pub fn limit_reached(&self) -> bool {
+        self.state.lock().unwrap().limit_reached()
+    }
+
+    /// Emits every queued report.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let reports = std::mem::take(&mut state.pending);
+        let colored = state.color_mode.is_enabled(self.is_terminal);
+
+        if !reports.is_empty() {
+            self.emitter
+                .emit(&mut state.print_stream, &self.source_map, &reports, colored)?;
+        }
+        state.print_stream.flush()
+    }
+
+    /// Returns the severity and text of the closing summary, such as
+    /// `aborting due to 3 previous errors; 2 warnings emitted`, or `None`
+    /// if nothing worth summarizing was reported.
+    pub fn summary(&self) -> Option<(ReportType, String)> {
+        let state = self.state.lock().unwrap();
+        let warnings = match state.warning_count {
+            1 => "1 warning emitted".to_string(),
+            count => format!("{} warnings emitted", count),
+        };
+
+        match (state.error_count, state.warning_count) {
+            (0, 0) => None,
+            (0, _) => Some((ReportType::Warning, warnings)),
+            (errors, warning_count) => {
+                let mut summary = match errors {
+                    1 => "aborting due to 1 previous error".to_string(),
+                    count => format!("aborting due to {} previous errors", count),
+                };
+                if warning_count > 0 {
+                    summary = format!("{}; {}", summary, warnings);
+                }
+                Some((ReportType::Error, summary))
+            }
+        }
+    }
+
+    /// Emits every queued report, then a note on the reports suppressed by
+    /// the error limit and the summary line.
+    pub fn finish(&self) -> io::Result<()> {
+        self.flush()?;
+
+        let (report_type, summary) = match self.summary() {
+            Some(summary) => summary,
+            None => return Ok(()),
+        };
+        let mut state = self.state.lock().unwrap();
+
+        match state.suppressed_count {
+            0 => {}
+            1 => self.write_summary_line(
+                &mut state,
+                ReportType::Note,
+                "1 further report was suppressed after reaching the error limit",
+            )?,
+            count => self.write_summary_line(
+                &mut state,
+                ReportType::Note,
+                &format!(
+                    "{} further reports were suppressed after reaching the error limit",
+                    count
+                ),
+            )?,
+        }
+        self.write_summary_line(&mut state, report_type, &summary)?;
+        state.print_stream.flush()
+    }
+
+    fn write_summary_line(
+        &self,
+        state: &mut SinkState,
+        report_type: ReportType,
+        message: &str,
+    ) -> io::Result<()> {
+        let style = match state.color_mode.is_enabled(self.is_terminal) {
+            true => self.theme().header_style(report_type),
+            false => Default::default(),
+        };
+
+        writeln!(
+            state.print_stream,
+            "{}{}{:#}: {}",
+            style,
+            report_type.name(),
+            style,
+            message
+        )
+    }
+}
+
+impl SinkState {
+    fn limit_reached(&self) -> bool {
+        self.max_errors
+            .is_some_and(|max_errors| self.error_count >= max_errors)
+    }
+}
+
+impl Debug for DiagnosticSink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+
+        f.debug_struct("DiagnosticSink")
+            .field("emitter", &self.emitter)
+            .field("error_count", &state.error_count)
+            .field("warning_count", &state.warning_count)
+            .field("max_errors", &state.max_errors)
+            .field("suppressed_count", &state.suppressed_count)
+            .field("warnings_as_errors", &state.warnings_as_errors)
+            .finish_non_exhaustive()
+    }
+}
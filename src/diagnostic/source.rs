@@ -1,38 +1,153 @@
-use super::line::Line;
-use std::{collections::HashMap, path::PathBuf};
+use super::{line::Line, position::Position, span::Span};
+use std::{io, path::PathBuf};
 
-pub static mut SOURCE_CACHE: Option<HashMap<PathBuf, Source>> = None;
+pub const BOM: char = '\u{feff}';
 
+/// A problem found while loading a source, at a byte offset into its text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoadWarning {
+    pub offset: usize,
+    pub message: String,
+}
+
+/// The text of a file split into lines.
+///
+/// A leading byte order mark is stripped, so byte offsets count from the
+/// first char after it. Lines end at `\n`, `\r\n` or a lone `\r`; the
+/// terminator is kept in `text` but not in the lines themselves.
 #[derive(Debug, Clone)]
 pub struct Source {
+    pub text: String,
     pub lines: Vec<Line>,
+    pub has_bom: bool,
+    /// Problems found while loading the text, such as invalid UTF-8.
+    pub warnings: Vec<LoadWarning>,
+    line_starts: Vec<usize>,
 }
 
 impl Source {
-    pub fn new(lines: Vec<Line>) -> Self {
-        Self { lines }
-    }
+    pub fn new(text: String) -> Self {
+        let (text, has_bom) = match text.strip_prefix(BOM) {
+            Some(text) => (text.to_string(), true),
+            None => (text, false),
+        };
+
+        let bytes = text.as_bytes();
+        let mut line_starts = vec![0];
+        line_starts.extend(bytes.iter().enumerate().filter_map(|(index, &byte)| {
+            let is_line_end =
+                byte == b'\n' || (byte == b'\r' && bytes.get(index + 1) != Some(&b'\n'));
+            is_line_end.then_some(index + 1)
+        }));
+        if line_starts.len() > 1 && *line_starts.last().unwrap() == text.len() {
+            line_starts.pop();
+        }
 
-    pub fn from_pathbuf(path: PathBuf) -> Option<Self> {
-        let source = std::fs::read_to_string(&path).ok()?;
-        let lines = source
-            .lines()
+        let lines = line_starts
+            .iter()
             .enumerate()
-            .map(|(line_number, line)| Line::new(line_number, 0, line.len(), line.to_string()))
+            .map(|(index, &start)| {
+                let end = line_starts.get(index + 1).map_or(text.len(), |&next| next);
+                let chars = text[start..end]
+                    .strip_suffix('\n')
+                    .unwrap_or(&text[start..end]);
+                let chars = chars.strip_suffix('\r').unwrap_or(chars);
+                Line::new(index + 1, start, chars.len(), chars.to_string())
+            })
             .collect();
-        Some(Self::new(lines))
+
+        Self {
+            text,
+            lines,
+            has_bom,
+            warnings: Vec::new(),
+            line_starts,
+        }
     }
 
-    pub fn sub_list(&self, from: usize, to: usize) -> Vec<Line> {
-        self.lines[from..to].to_vec()
+    /// Reads a file from disk. Invalid UTF-8 doesn't fail the read: the bad
+    /// bytes are replaced with U+FFFD and a warning is recorded, since byte
+    /// offsets past them no longer match the file.
+    pub fn from_pathbuf(path: PathBuf) -> io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+
+        match String::from_utf8(bytes) {
+            Ok(text) => Ok(Self::new(text)),
+            Err(error) => {
+                let offset = error.utf8_error().valid_up_to();
+                let mut source = Self::new(String::from_utf8_lossy(error.as_bytes()).to_string());
+                let bom_length = if source.has_bom { BOM.len_utf8() } else { 0 };
+                source.warnings.push(LoadWarning {
+                    offset: offset - bom_length,
+                    message: format!(
+                        "file is not valid UTF-8 (first invalid byte at offset {}); \
+                         invalid bytes were replaced with U+FFFD",
+                        offset
+                    ),
+                });
+                Ok(source)
+            }
+        }
+    }
+
+    /// Whether the text was loaded lossily and no longer matches the file
+    /// byte for byte.
+    pub fn is_lossy(&self) -> bool {
+        !self.warnings.is_empty()
     }
 
     pub fn from_string(source: String) -> Self {
-        let lines = source
-            .lines()
-            .enumerate()
-            .map(|(line_number, line)| Line::new(line_number, 0, line.len(), line.to_string()))
-            .collect();
-        Self::new(lines)
+        Self::new(source)
+    }
+
+    /// Returns the lines in `from..to` (0-based indices), clamped to the
+    /// lines the source actually has.
+    pub fn sub_list(&self, from: usize, to: usize) -> Vec<Line> {
+        let to = to.min(self.lines.len());
+        self.lines
+            .get(from.min(to)..to)
+            .unwrap_or_default()
+            .to_vec()
+    }
+
+    /// Returns the 1-based line, or `None` if it is out of range.
+    pub fn line(&self, line_number: usize) -> Option<&Line> {
+        self.lines.get(line_number.checked_sub(1)?)
+    }
+
+    /// Converts a byte offset into a 1-based line and a 0-based column in
+    /// chars. Offsets past the end of the text are clamped to it, offsets
+    /// inside a multi-byte char resolve to that char.
+    pub fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line_index = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line = &self.lines[line_index];
+        let column = self.text[line.offset..offset.min(line.offset + line.length)]
+            .chars()
+            .count();
+
+        Position::new(line.line_number, column)
+    }
+
+    /// Converts a 1-based line and a 0-based char column back into a byte
+    /// offset. The column may point one past the last char of the line.
+    pub fn offset(&self, position: Position) -> Option<usize> {
+        let line = self.line(position.line)?;
+
+        line.chars
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain(std::iter::once(line.length))
+            .nth(position.column)
+            .map(|index| line.offset + index)
+    }
+
+    /// Creates a resolved span from a byte range.
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        Span::byte_range(start, end).resolve(self)
     }
 }
@@ -0,0 +1,155 @@
+use super::source::{LoadWarning, Source};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock, RwLock},
+};
+
+/// A cheap handle to a file registered in a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(usize);
+
+impl FileId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug)]
+struct SourceFile {
+    name: RwLock<String>,
+    path: Option<PathBuf>,
+    source: OnceLock<Result<Arc<Source>, String>>,
+}
+
+/// Owns every [`Source`] a compilation session has loaded.
+///
+/// Files registered by path are read lazily, once, on first access. The map
+/// is internally synchronized, so an `Arc<SourceMap>` can be handed to worker
+/// threads that emit diagnostics.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: RwLock<Vec<Arc<SourceFile>>>,
+    paths: RwLock<HashMap<PathBuf, FileId>>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a file on disk, returning the existing id if the path was
+    /// already registered. The file is not read until it is first needed.
+    pub fn add_path(&self, path: &Path) -> FileId {
+        let mut paths = self.paths.write().unwrap();
+
+        if let Some(file_id) = paths.get(path) {
+            return *file_id;
+        }
+
+        let file_id = self.push(SourceFile {
+            name: RwLock::new(path.to_string_lossy().to_string()),
+            path: Some(path.to_path_buf()),
+            source: OnceLock::new(),
+        });
+        paths.insert(path.to_path_buf(), file_id);
+        file_id
+    }
+
+    /// Registers an in-memory source under a display name.
+    pub fn add_source(&self, name: String, source: String) -> FileId {
+        self.push(SourceFile {
+            name: RwLock::new(name),
+            path: None,
+            source: OnceLock::from(Ok(Arc::new(Source::from_string(source)))),
+        })
+    }
+
+    fn push(&self, file: SourceFile) -> FileId {
+        let mut files = self.files.write().unwrap();
+        files.push(Arc::new(file));
+        FileId(files.len() - 1)
+    }
+
+    fn file(&self, file_id: FileId) -> Option<Arc<SourceFile>> {
+        self.files.read().unwrap().get(file_id.0).cloned()
+    }
+
+    /// Returns the source of a file, reading it from disk on first access.
+    /// Returns `None` for unknown ids and files that could not be read; see
+    /// [`SourceMap::load_error`] for why.
+    pub fn get(&self, file_id: FileId) -> Option<Arc<Source>> {
+        self.load(file_id)?.ok()
+    }
+
+    /// Returns why a file could not be read, or `None` if it was read or the
+    /// id is unknown.
+    pub fn load_error(&self, file_id: FileId) -> Option<String> {
+        self.load(file_id)?.err()
+    }
+
+    /// Returns the problems found while reading a file, such as invalid
+    /// UTF-8.
+    pub fn load_warnings(&self, file_id: FileId) -> Vec<LoadWarning> {
+        self.get(file_id)
+            .map(|source| source.warnings.clone())
+            .unwrap_or_default()
+    }
+
+    fn load(&self, file_id: FileId) -> Option<Result<Arc<Source>, String>> {
+        let file = self.file(file_id)?;
+
+        Some(
+            file.source
+                .get_or_init(|| match &file.path {
+                    Some(path) => Source::from_pathbuf(path.clone())
+                        .map(Arc::new)
+                        .map_err(|error| error.to_string()),
+                    None => Err("no path or source registered".to_string()),
+                })
+                .clone(),
+        )
+    }
+
+    pub fn name(&self, file_id: FileId) -> Option<String> {
+        self.file(file_id)
+            .map(|file| file.name.read().unwrap().clone())
+    }
+
+    /// Changes the name a file is displayed under in diagnostics.
+    pub fn set_name(&self, file_id: FileId, name: String) {
+        if let Some(file) = self.file(file_id) {
+            *file.name.write().unwrap() = name;
+        }
+    }
+
+    /// Returns the display name of a file, or `"Unknown"` for unknown ids.
+    pub fn display_name(&self, file_id: FileId) -> String {
+        self.name(file_id).unwrap_or_else(|| "Unknown".to_string())
+    }
+
+    pub fn path(&self, file_id: FileId) -> Option<PathBuf> {
+        self.file(file_id).and_then(|file| file.path.clone())
+    }
+
+    pub fn file_id(&self, path: &Path) -> Option<FileId> {
+        self.paths.read().unwrap().get(path).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Returns the display name of an optional file, or `"Unknown"` if there
+/// is none.
+pub fn file_name(source_map: &SourceMap, file_id: Option<FileId>) -> String {
+    match file_id {
+        Some(file_id) => source_map.display_name(file_id),
+        None => "Unknown".to_string(),
+    }
+}
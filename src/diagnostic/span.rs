@@ -1,9 +1,11 @@
-use super::position::Position;
+use super::{line::Line, position::Position, source::Source};
+use std::cmp::{max, min};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Span {
     pub start_position: Position,
     pub end_position: Position,
+    pub offsets: Option<(usize, usize)>,
 }
 
 impl Span {
@@ -11,6 +13,17 @@ impl Span {
         Self {
             start_position,
             end_position,
+            offsets: None,
+        }
+    }
+
+    /// Creates a span from a byte range into its file. Line and column
+    /// positions are filled in by [`Span::resolve`].
+    pub fn byte_range(start: usize, end: usize) -> Self {
+        Self {
+            start_position: Position::new(0, 0),
+            end_position: Position::new(0, 0),
+            offsets: Some((start, end)),
         }
     }
 
@@ -19,10 +32,10 @@ impl Span {
     }
 
     pub fn single_line(line_number: usize, start: usize, end: usize) -> Self {
-        Self {
-            start_position: Position::new(line_number, start),
-            end_position: Position::new(line_number, end),
-        }
+        Self::new(
+            Position::new(line_number, start),
+            Position::new(line_number, end),
+        )
     }
 
     pub fn multiple_line(
@@ -31,27 +44,65 @@ impl Span {
         end_line_number: usize,
         end: usize,
     ) -> Self {
-        Self {
-            start_position: Position::new(start_line_number, start),
-            end_position: Position::new(end_line_number, end),
+        Self::new(
+            Position::new(start_line_number, start),
+            Position::new(end_line_number, end),
+        )
+    }
+
+    /// Fills in whichever of the line/column positions or byte offsets the
+    /// span was not created with.
+    pub fn resolve(&self, source: &Source) -> Span {
+        match self.offsets {
+            Some((start, end)) => Span {
+                start_position: source.position(start),
+                end_position: source.position(end),
+                offsets: Some((start, end)),
+            },
+            None => Span {
+                start_position: self.start_position,
+                end_position: self.end_position,
+                offsets: source
+                    .offset(self.start_position)
+                    .zip(source.offset(self.end_position)),
+            },
         }
     }
 
     pub fn expand(&self, other: Span) -> Span {
-        let copied = self.clone();
+        let copied = *self;
 
-        if other.end_position.line < self.start_position.line {
+        if other.end_position.line < self.start_position.line
+            || (other.end_position.line == self.start_position.line
+                && other.end_position.column < self.start_position.column)
+        {
             return copied;
-        } else if other.end_position.line == self.start_position.line {
-            if other.end_position.column < self.start_position.column {
-                return copied;
-            }
         }
 
         let start_position = self.start_position;
         let end_position = other.end_position;
 
-        Span::new(start_position, end_position)
+        Span {
+            start_position,
+            end_position,
+            offsets: self
+                .offsets
+                .zip(other.offsets)
+                .map(|((start, _), (_, end))| (start, end)),
+        }
+    }
+
+    /// Returns the smallest span covering both spans.
+    pub fn union(&self, other: &Span) -> Span {
+        Span {
+            start_position: min(self.start_position, other.start_position),
+            end_position: max(self.end_position, other.end_position),
+            offsets: self.offsets.zip(other.offsets).map(
+                |((start, end), (other_start, other_end))| {
+                    (min(start, other_start), max(end, other_end))
+                },
+            ),
+        }
     }
 
     pub fn is_multiline(&self) -> bool {
@@ -71,4 +122,25 @@ impl Span {
 
         self.end_position.column - self.start_position.column
     }
+
+    /// Returns the char columns of `line` covered by the span, or an empty
+    /// range at column 0 if the span does not touch the line.
+    pub fn columns_on(&self, line: &Line) -> (usize, usize) {
+        if !self.is_in(line.line_number) {
+            return (0, 0);
+        }
+
+        let start = if self.start_position.line == line.line_number {
+            self.start_position.column
+        } else {
+            0
+        };
+        let end = if self.end_position.line == line.line_number {
+            self.end_position.column
+        } else {
+            line.chars.chars().count()
+        };
+
+        (start, max(start, end))
+    }
 }
@@ -0,0 +1,115 @@
+use super::{source::Source, span::Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Applicability {
+    /// The replacement is definitely what the user intended and can be applied
+    /// without review.
+    MachineApplicable,
+    /// The replacement may be what the user intended, but it is uncertain.
+    MaybeIncorrect,
+    /// The replacement contains placeholders like `(...)` that the user has to
+    /// fill in.
+    HasPlaceholders,
+}
+
+impl Applicability {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+            Applicability::HasPlaceholders => "has-placeholders",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Suggestion {
+    pub span: Span,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    pub fn new(span: Span, replacement: String, applicability: Applicability) -> Self {
+        Self {
+            span,
+            replacement,
+            applicability,
+        }
+    }
+
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == Applicability::MachineApplicable
+    }
+
+    /// Returns the lines touched by the suggestion, as they read before and
+    /// after applying it, joined with `\n` whatever the file's line endings.
+    /// `None` if the span is unresolved or out of bounds.
+    pub fn diff(&self, source: &Source) -> Option<(String, String)> {
+        let (start, end) = self.span.offsets?;
+        let first_line = source.line(self.span.start_position.line)?;
+        let last_line = source.line(self.span.end_position.line)?;
+
+        if start > end
+            || start < first_line.offset
+            || start > first_line.offset + first_line.length
+            || end < last_line.offset
+            || end > last_line.offset + last_line.length
+        {
+            return None;
+        }
+
+        let before = source
+            .sub_list(first_line.line_number - 1, last_line.line_number)
+            .into_iter()
+            .map(|line| line.chars)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let after = format!(
+            "{}{}{}",
+            first_line.chars.get(..start - first_line.offset)?,
+            self.replacement.replace("\r\n", "\n"),
+            last_line.chars.get(end - last_line.offset..)?
+        );
+
+        Some((before, after))
+    }
+}
+
+/// Applies every machine-applicable suggestion to `source`, returning the new
+/// text and the number of suggestions applied. Suggestions overlapping one
+/// applied earlier in the file are skipped.
+pub fn apply_suggestions(source: &Source, suggestions: &[&Suggestion]) -> (String, usize) {
+    let mut replacements: Vec<(usize, usize, &str)> = suggestions
+        .iter()
+        .filter(|suggestion| suggestion.is_machine_applicable())
+        .filter_map(|suggestion| {
+            let (start, end) = suggestion.span.offsets?;
+            let valid = start <= end
+                && end <= source.text.len()
+                && source.text.is_char_boundary(start)
+                && source.text.is_char_boundary(end);
+            valid.then_some((start, end, suggestion.replacement.as_str()))
+        })
+        .collect();
+    replacements.sort();
+    replacements.dedup();
+
+    let mut fixed = String::new();
+    let mut last_end = 0;
+    let mut applied = 0;
+
+    for (start, end, replacement) in replacements {
+        if start < last_end {
+            continue;
+        }
+
+        fixed.push_str(&source.text[last_end..start]);
+        fixed.push_str(replacement);
+        last_end = end;
+        applied += 1;
+    }
+    fixed.push_str(&source.text[last_end..]);
+
+    (fixed, applied)
+}
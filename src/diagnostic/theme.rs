@@ -0,0 +1,374 @@
+use super::{
+    char_set::{CharacterSet, ASCII, HEAVY, UNICODE},
+    report::ReportType,
+    report_builder::LABEL_PALETTE,
+};
+use anstyle::{Ansi256Color, AnsiColor, Color, Effects, RgbColor, Style};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::{path::Path, str::FromStr};
+
+/// How the human renderer draws a report: the characters of its boxes and
+/// rails and the style of each element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub character_set: CharacterSet,
+    pub error: Style,
+    pub warning: Style,
+    pub note: Style,
+    pub help: Style,
+    pub info: Style,
+    pub bug: Style,
+    /// Line numbers, the box around snippets and footer markers.
+    pub gutter: Style,
+    pub hint: Style,
+    /// Lines a suggestion removes.
+    pub removed: Style,
+    /// Lines a suggestion adds.
+    pub added: Style,
+    /// Colors handed out to labels that don't pick their own.
+    pub label_palette: Vec<Color>,
+}
+
+lazy_static! {
+    pub static ref ROUNDED: Theme = Theme::new(*UNICODE);
+    pub static ref HEAVY_BOX: Theme = Theme::new(*HEAVY);
+    pub static ref PLAIN_ASCII: Theme = Theme::new(*ASCII);
+    pub static ref HIGH_CONTRAST: Theme = Theme {
+        error: ansi(AnsiColor::BrightRed).bold(),
+        warning: ansi(AnsiColor::BrightYellow).bold(),
+        note: ansi(AnsiColor::BrightGreen).bold(),
+        help: ansi(AnsiColor::BrightCyan).bold(),
+        info: ansi(AnsiColor::BrightBlue).bold(),
+        bug: ansi(AnsiColor::BrightMagenta).effects(Effects::BOLD | Effects::UNDERLINE),
+        gutter: ansi(AnsiColor::BrightWhite).bold(),
+        hint: ansi(AnsiColor::BrightCyan).bold(),
+        removed: ansi(AnsiColor::BrightRed).bold(),
+        added: ansi(AnsiColor::BrightGreen).bold(),
+        label_palette: [
+            AnsiColor::BrightYellow,
+            AnsiColor::BrightCyan,
+            AnsiColor::BrightMagenta,
+            AnsiColor::BrightGreen,
+            AnsiColor::BrightRed,
+            AnsiColor::BrightWhite,
+        ]
+        .into_iter()
+        .map(Color::Ansi)
+        .collect(),
+        ..Theme::new(*UNICODE)
+    };
+    /// Uses the Okabe-Ito palette, which stays distinguishable under the
+    /// common forms of color blindness.
+    pub static ref COLOR_BLIND_SAFE: Theme = Theme {
+        error: rgb(0xd5, 0x5e, 0x00),
+        warning: rgb(0xe6, 0x9f, 0x00),
+        note: rgb(0x00, 0x9e, 0x73),
+        help: rgb(0x56, 0xb4, 0xe9),
+        info: rgb(0x00, 0x72, 0xb2),
+        bug: rgb(0xcc, 0x79, 0xa7).effects(Effects::BOLD | Effects::UNDERLINE),
+        hint: rgb(0x56, 0xb4, 0xe9),
+        removed: rgb(0xd5, 0x5e, 0x00),
+        added: rgb(0x00, 0x72, 0xb2),
+        label_palette: [
+            (0xe6, 0x9f, 0x00),
+            (0x56, 0xb4, 0xe9),
+            (0x00, 0x9e, 0x73),
+            (0xf0, 0xe4, 0x42),
+            (0x00, 0x72, 0xb2),
+            (0xd5, 0x5e, 0x00),
+            (0xcc, 0x79, 0xa7),
+        ]
+        .into_iter()
+        .map(|(r, g, b)| Color::Rgb(RgbColor(r, g, b)))
+        .collect(),
+        ..Theme::new(*UNICODE)
+    };
+}
+
+/// The names built-in themes are selected by, as accepted by `--theme`.
+pub const BUILTIN_THEMES: [&str; 5] = [
+    "rounded",
+    "heavy",
+    "ascii",
+    "high-contrast",
+    "color-blind-safe",
+];
+
+impl Theme {
+    /// Creates a theme drawing with `character_set` in the default colors.
+    pub fn new(character_set: CharacterSet) -> Self {
+        Self {
+            character_set,
+            error: ReportType::Error.header_style(),
+            warning: ReportType::Warning.header_style(),
+            note: ReportType::Note.header_style(),
+            help: ReportType::Help.header_style(),
+            info: ReportType::Info.header_style(),
+            bug: ReportType::Bug.header_style(),
+            gutter: ansi(AnsiColor::BrightBlack),
+            hint: ansi(AnsiColor::BrightBlue),
+            removed: ansi(AnsiColor::Red),
+            added: ansi(AnsiColor::Green),
+            label_palette: LABEL_PALETTE.to_vec(),
+        }
+    }
+
+    pub fn header_style(&self, report_type: ReportType) -> Style {
+        match report_type {
+            ReportType::Error => self.error,
+            ReportType::Warning => self.warning,
+            ReportType::Note => self.note,
+            ReportType::Help => self.help,
+            ReportType::Info => self.info,
+            ReportType::Bug => self.bug,
+        }
+    }
+
+    /// Parses a theme from TOML. Every key is optional and overrides the
+    /// theme named by `base`, or the rounded theme if there is none:
+    ///
+    /// ```toml
+    /// base = "heavy"
+    /// label_palette = ["bright_red", "ansi256:208", "#56b4e9"]
+    ///
+    /// [characters]
+    /// underline = "~"
+    ///
+    /// [styles]
+    /// gutter = "dimmed"
+    /// error = "bold underline red"
+    /// ```
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let config: ThemeConfig = toml::from_str(text).map_err(|error| error.to_string())?;
+        let mut theme = match &config.base {
+            Some(base) => base.parse()?,
+            None => Theme::default(),
+        };
+
+        let characters = config.characters;
+        let character_set = &mut theme.character_set;
+        for (field, character) in [
+            (&mut character_set.right_arrow, characters.right_arrow),
+            (&mut character_set.horizontal_bar, characters.horizontal_bar),
+            (&mut character_set.vertical_bar, characters.vertical_bar),
+            (&mut character_set.cross_bar, characters.cross_bar),
+            (&mut character_set.under_bar, characters.under_bar),
+            (&mut character_set.left_cross, characters.left_cross),
+            (
+                &mut character_set.vertical_bar_breaking,
+                characters.vertical_bar_breaking,
+            ),
+            (
+                &mut character_set.vertical_ellipsis,
+                characters.vertical_ellipsis,
+            ),
+            (
+                &mut character_set.horizontal_ellipsis,
+                characters.horizontal_ellipsis,
+            ),
+            (&mut character_set.left_top, characters.left_top),
+            (&mut character_set.left_bottom, characters.left_bottom),
+            (&mut character_set.right_bottom, characters.right_bottom),
+            (&mut character_set.underline, characters.underline),
+        ] {
+            if let Some(character) = character {
+                *field = character;
+            }
+        }
+
+        let styles = config.styles;
+        for (field, style) in [
+            (&mut theme.error, styles.error),
+            (&mut theme.warning, styles.warning),
+            (&mut theme.note, styles.note),
+            (&mut theme.help, styles.help),
+            (&mut theme.info, styles.info),
+            (&mut theme.bug, styles.bug),
+            (&mut theme.gutter, styles.gutter),
+            (&mut theme.hint, styles.hint),
+            (&mut theme.removed, styles.removed),
+            (&mut theme.added, styles.added),
+        ] {
+            if let Some(style) = style {
+                *field = parse_style(&style)?;
+            }
+        }
+
+        if let Some(label_palette) = config.label_palette {
+            theme.label_palette = label_palette
+                .iter()
+                .map(|color| parse_color(color))
+                .collect::<Result<_, _>>()?;
+        }
+
+        Ok(theme)
+    }
+
+    /// Reads a theme from a TOML file, as described in [`Theme::from_toml`].
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("failed to read theme `{}`: {}", path.display(), error))?;
+
+        Self::from_toml(&text)
+            .map_err(|error| format!("invalid theme `{}`: {}", path.display(), error))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ROUNDED.clone()
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    /// Looks up a built-in theme by name.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "rounded" => Ok(ROUNDED.clone()),
+            "heavy" => Ok(HEAVY_BOX.clone()),
+            "ascii" => Ok(PLAIN_ASCII.clone()),
+            "high-contrast" => Ok(HIGH_CONTRAST.clone()),
+            "color-blind-safe" => Ok(COLOR_BLIND_SAFE.clone()),
+            _ => Err(format!(
+                "unknown theme `{}`, expected one of {}",
+                name,
+                BUILTIN_THEMES.join(", ")
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeConfig {
+    base: Option<String>,
+    label_palette: Option<Vec<String>>,
+    #[serde(default)]
+    characters: CharacterConfig,
+    #[serde(default)]
+    styles: StyleConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CharacterConfig {
+    right_arrow: Option<char>,
+    horizontal_bar: Option<char>,
+    vertical_bar: Option<char>,
+    cross_bar: Option<char>,
+    under_bar: Option<char>,
+    left_cross: Option<char>,
+    vertical_bar_breaking: Option<char>,
+    vertical_ellipsis: Option<char>,
+    horizontal_ellipsis: Option<char>,
+    left_top: Option<char>,
+    left_bottom: Option<char>,
+    right_bottom: Option<char>,
+    underline: Option<char>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleConfig {
+    error: Option<String>,
+    warning: Option<String>,
+    note: Option<String>,
+    help: Option<String>,
+    info: Option<String>,
+    bug: Option<String>,
+    gutter: Option<String>,
+    hint: Option<String>,
+    removed: Option<String>,
+    added: Option<String>,
+}
+
+/// Parses a color written the way [`color_name`] writes it.
+pub fn parse_color(name: &str) -> Result<Color, String> {
+    if let Some(index) = name.strip_prefix("ansi256:") {
+        return index
+            .parse()
+            .map(|index| Color::Ansi256(Ansi256Color(index)))
+            .map_err(|_| format!("invalid 256-color index `{}`", index));
+    }
+
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |index: usize| {
+            hex.get(index..index + 2)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+        };
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(RgbColor(r, g, b))),
+            _ => Err(format!("invalid RGB color `{}`", name)),
+        };
+    }
+
+    (0..16)
+        .filter_map(|index| Ansi256Color(index).into_ansi())
+        .map(Color::Ansi)
+        .find(|color| color_name(*color) == name)
+        .ok_or_else(|| format!("unknown color `{}`", name))
+}
+
+/// Parses a style written as space-separated effects and at most one color,
+/// such as `bold underline bright_red`. `none` is the empty style.
+pub fn parse_style(text: &str) -> Result<Style, String> {
+    let mut style = Style::new();
+
+    for word in text.split_whitespace() {
+        style = match word {
+            "none" => style,
+            "bold" => style.bold(),
+            "dimmed" => style.dimmed(),
+            "italic" => style.italic(),
+            "underline" => style.underline(),
+            "strikethrough" => style.strikethrough(),
+            color if style.get_fg_color().is_some() => {
+                return Err(format!("style `{}` has a second color `{}`", text, color));
+            }
+            color => style.fg_color(Some(parse_color(color)?)),
+        };
+    }
+
+    Ok(style)
+}
+
+fn ansi(color: AnsiColor) -> Style {
+    Style::new().fg_color(Some(Color::Ansi(color)))
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> Style {
+    Style::new().fg_color(Some(Color::Rgb(RgbColor(r, g, b))))
+}
+
+/// Names a color the way it would be written in a config file: ANSI colors
+/// in snake case, 256-color indices as `ansi256:N` and RGB as `#rrggbb`.
+pub fn color_name(color: Color) -> String {
+    match color {
+        Color::Ansi(color) => {
+            let name = match color {
+                AnsiColor::Black => "black",
+                AnsiColor::Red => "red",
+                AnsiColor::Green => "green",
+                AnsiColor::Yellow => "yellow",
+                AnsiColor::Blue => "blue",
+                AnsiColor::Magenta => "magenta",
+                AnsiColor::Cyan => "cyan",
+                AnsiColor::White => "white",
+                AnsiColor::BrightBlack => "bright_black",
+                AnsiColor::BrightRed => "bright_red",
+                AnsiColor::BrightGreen => "bright_green",
+                AnsiColor::BrightYellow => "bright_yellow",
+                AnsiColor::BrightBlue => "bright_blue",
+                AnsiColor::BrightMagenta => "bright_magenta",
+                AnsiColor::BrightCyan => "bright_cyan",
+                AnsiColor::BrightWhite => "bright_white",
+            };
+            name.to_string()
+        }
+        Color::Ansi256(color) => format!("ansi256:{}", color.index()),
+        Color::Rgb(color) => format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b()),
+    }
+}
@@ -1 +1 @@
-pub mod diagnostic;
\ No newline at end of file
+pub mod diagnostic;
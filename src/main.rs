@@ -1,18 +1,89 @@
 use anstyle::{AnsiColor, Color};
-use std::{collections::HashMap, io::stdout, path::Path};
+use std::{env, path::Path, process::exit, sync::Arc};
 use yakou::diagnostic::{
-    char_set::ASCII, report_builder::FileReportBuilder, source::SOURCE_CACHE, span::Span,
+    codes,
+    emitter::ErrorFormat,
+    report_builder::FileReportBuilder,
+    sink::DiagnosticSink,
+    source_map::SourceMap,
+    span::Span,
+    theme::{Theme, PLAIN_ASCII},
 };
 
 fn main() {
-    unsafe { SOURCE_CACHE = Some(HashMap::new()) }
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
 
-    let path = Path::new("tests/test.yk");
+    match args.as_slice() {
+        ["explain" | "--explain", code] => explain(code),
+        flags => report(
+            parse_error_format(flags),
+            parse_warnings_as_errors(flags),
+            parse_theme(flags),
+        ),
+    }
+}
+
+fn parse_error_format(flags: &[&str]) -> ErrorFormat {
+    let mut error_format = ErrorFormat::default();
+
+    for flag in flags {
+        if let Some(format) = flag.strip_prefix("--error-format=") {
+            error_format = format.parse().unwrap_or_else(|error| {
+                eprintln!("error: {}", error);
+                exit(1);
+            });
+        }
+    }
+
+    error_format
+}
+
+fn parse_warnings_as_errors(flags: &[&str]) -> bool {
+    flags.contains(&"-Werror") || flags.windows(2).any(|pair| pair == ["-W", "error"])
+}
+
+/// Accepts either a built-in theme name or the path of a TOML theme file.
+fn parse_theme(flags: &[&str]) -> Theme {
+    let mut theme = PLAIN_ASCII.clone();
+
+    for flag in flags {
+        if let Some(name) = flag.strip_prefix("--theme=") {
+            theme = name
+                .parse()
+                .or_else(|error| match Path::new(name).is_file() {
+                    true => Theme::load(Path::new(name)),
+                    false => Err(error),
+                })
+                .unwrap_or_else(|error| {
+                    eprintln!("error: {}", error);
+                    exit(1);
+                });
+        }
+    }
 
-    FileReportBuilder::source_file(path)
-        .character_set(*ASCII)
+    theme
+}
+
+fn explain(code: &str) {
+    match codes::lookup(&code.to_uppercase()) {
+        Some(code) => println!("{}: {}\n\n{}", code.code, code.summary, code.explanation),
+        None => {
+            eprintln!("error: `{}` is not a valid error code", code);
+            exit(1);
+        }
+    }
+}
+
+fn report(error_format: ErrorFormat, warnings_as_errors: bool, theme: Theme) {
+    let source_map = Arc::new(SourceMap::new());
+    let file_id = source_map.add_path(Path::new("tests/test.yk"));
+
+    let builder = FileReportBuilder::new(source_map.clone(), file_id)
+        .theme(theme)
+        .error_format(error_format)
         .error(Span::single_line(1, 0, 6), "test or smth".to_string())
-        .tag("E01".to_string())
+        .code(&codes::E01)
         // .label(Span::multiple_line(1, 0, 6, 0), "LOL".to_string())
         // .color(Color::Ansi(AnsiColor::Red))
         // .build()
@@ -27,9 +98,27 @@ fn main() {
         .color(Color::Ansi(AnsiColor::BrightCyan))
         .hint("impl it!".to_string())
         .build()
+        .note("classes are final by default".to_string())
+        .help("add `open` to the declaration\nor remove the implementation".to_string())
         // .label(Span::multiple_line(4, 0, 6, 0), "kek".to_string())
         // .hint("omegalul".to_string())
         // .build()
-        .build()
-        .print(&mut stdout());
+        .build();
+
+    let sink = DiagnosticSink::stderr(source_map, error_format.emitter(builder.human_emitter()))
+        .warnings_as_errors(warnings_as_errors);
+    builder.send(&sink);
+
+    // Machine-readable formats must stay parseable, so only people get a summary.
+    let result = match error_format {
+        ErrorFormat::Human | ErrorFormat::Short => sink.finish(),
+        _ => sink.flush(),
+    };
+    result.unwrap_or_else(|error| {
+        eprintln!("error: failed to print diagnostics: {}", error);
+        exit(1);
+    });
+    if sink.has_errors() {
+        exit(1);
+    }
 }
@@ -0,0 +1,117 @@
+//! Applying machine-applicable suggestions, in memory and on disk.
+
+use std::{fs, path::PathBuf};
+use yakou::diagnostic::{
+    report_builder::FileReportBuilder,
+    source::Source,
+    span::Span,
+    suggestion::{Applicability, Suggestion},
+};
+
+fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("yakou-{}-{}", std::process::id(), name));
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn only_non_overlapping_machine_applicable_suggestions_are_applied() {
+    let builder = FileReportBuilder::new_source("let a = b + c;\n".to_string())
+        .error(Span::single_line(1, 4, 5), "fixes".to_string())
+        .label(Span::single_line(1, 4, 5), "rename".to_string())
+        .suggestion(
+            Span::single_line(1, 4, 5),
+            "x".to_string(),
+            Applicability::MachineApplicable,
+        )
+        .suggestion(
+            Span::single_line(1, 4, 9),
+            "y = z".to_string(),
+            Applicability::MachineApplicable,
+        )
+        .build()
+        .label(Span::single_line(1, 8, 9), "maybe".to_string())
+        .suggestion(
+            Span::single_line(1, 8, 9),
+            "maybe".to_string(),
+            Applicability::MaybeIncorrect,
+        )
+        .build()
+        .label(Span::single_line(1, 12, 13), "insert".to_string())
+        .suggestion(
+            Span::single_line(1, 13, 13),
+            ".0".to_string(),
+            Applicability::MachineApplicable,
+        )
+        .build()
+        .build();
+
+    assert_eq!(
+        builder.fixed_source(builder.file_id()),
+        Some(("let x = b + c.0;\n".to_string(), 2))
+    );
+}
+
+#[test]
+fn fixes_keep_the_bom_and_crlf_line_endings() {
+    let path = temp_file("fix-bom.yk", b"\xef\xbb\xbflet a = 1;\r\nlet b = a;\r\n");
+    let applied = FileReportBuilder::new_file(&path)
+        .error(Span::single_line(2, 8, 9), "rename".to_string())
+        .label(Span::single_line(2, 8, 9), "here".to_string())
+        .suggestion(
+            Span::single_line(2, 8, 9),
+            "c".to_string(),
+            Applicability::MachineApplicable,
+        )
+        .build()
+        .build()
+        .apply_fixes()
+        .unwrap();
+
+    assert_eq!(applied, 1);
+    assert_eq!(
+        fs::read(&path).unwrap(),
+        b"\xef\xbb\xbflet a = 1;\r\nlet b = c;\r\n"
+    );
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn lossy_files_are_not_rewritten() {
+    let bytes = b"let \xff = 1;\n";
+    let path = temp_file("fix-lossy.yk", bytes);
+    let applied = FileReportBuilder::new_file(&path)
+        .error(Span::single_line(1, 8, 9), "change".to_string())
+        .label(Span::single_line(1, 8, 9), "here".to_string())
+        .suggestion(
+            Span::single_line(1, 8, 9),
+            "2".to_string(),
+            Applicability::MachineApplicable,
+        )
+        .build()
+        .build()
+        .apply_fixes()
+        .unwrap();
+
+    assert_eq!(applied, 0);
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn diff_of_a_multi_line_replacement() {
+    let source = Source::new("fn f() {\n    a();\n    b();\n}\n".to_string());
+    let suggestion = Suggestion::new(
+        Span::multiple_line(2, 4, 3, 8).resolve(&source),
+        "c(\n        d,\n    );".to_string(),
+        Applicability::MachineApplicable,
+    );
+
+    assert_eq!(
+        suggestion.diff(&source),
+        Some((
+            "    a();\n    b();".to_string(),
+            "    c(\n        d,\n    );".to_string()
+        ))
+    );
+}
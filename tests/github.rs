@@ -0,0 +1,35 @@
+//! Workflow commands written for GitHub Actions.
+
+use yakou::diagnostic::{
+    emitter::ErrorFormat, report::ReportType, report_builder::FileReportBuilder, span::Span,
+};
+
+#[test]
+fn commands_match_report_spans_and_escape_special_characters() {
+    let output = FileReportBuilder::new_source("first line\nsecond\nthird\n".to_string())
+        .source_name("dir\\a,b:c.yk".to_string())
+        .error_format(ErrorFormat::Github)
+        .error(
+            Span::multiple_line(1, 5, 3, 3),
+            "100% wrong\nreally".to_string(),
+        )
+        .tag("E:1,2".to_string())
+        .note("see: here".to_string())
+        .child(
+            ReportType::Warning,
+            Span::single_line(2, 2, 2),
+            "empty".to_string(),
+        )
+        .build_child()
+        .build()
+        .render_to_string()
+        .unwrap();
+
+    assert_eq!(
+        output,
+        "\
+::error file=dir/a%2Cb%3Ac.yk,line=1,col=6,endLine=3,endColumn=3,title=E%3A1%2C2::100%25 wrong%0Areally%0Anote: see: here
+::warning file=dir/a%2Cb%3Ac.yk,line=2,col=3,endLine=2,endColumn=3,title=warning::empty
+"
+    );
+}
@@ -0,0 +1,59 @@
+use anstyle::{Ansi256Color, Color, Style};
+use yakou::diagnostic::{
+    html, report::ReportType, report_builder::FileReportBuilder, span::Span,
+    suggestion::Applicability,
+};
+
+#[test]
+fn every_span_is_closed() {
+    let mut output = Vec::new();
+    FileReportBuilder::new_source("class Yakou\n\nimpl Yakou {\n}\n".to_string())
+        .error(
+            Span::single_line(3, 5, 10),
+            "class `Yakou` is final".to_string(),
+        )
+        .label(Span::single_line(3, 0, 4), "implemented here".to_string())
+        .color(Color::Ansi256(Ansi256Color(208)))
+        .hint("mark the class as open".to_string())
+        .suggestion(
+            Span::multiple_line(1, 0, 3, 4),
+            "open class Yakou\n\nimpl".to_string(),
+            Applicability::MachineApplicable,
+        )
+        .build()
+        .label(Span::multiple_line(3, 0, 4, 1), "in this block".to_string())
+        .build()
+        .note("classes are final by default".to_string())
+        .child(
+            ReportType::Note,
+            Span::single_line(1, 0, 11),
+            "declared here".to_string(),
+        )
+        .build()
+        .print_html(&mut output, false)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("suggestion"));
+    assert_eq!(
+        output.matches("<span").count(),
+        output.matches("</span>").count(),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn extended_256_colors_are_inlined() {
+    let style = |index| Style::new().fg_color(Some(Color::Ansi256(Ansi256Color(index))));
+
+    assert_eq!(
+        html::open_span(&style(208)),
+        "<span class=\"\" style=\"color: #ff8700\">"
+    );
+    assert_eq!(
+        html::open_span(&style(244)),
+        "<span class=\"\" style=\"color: #808080\">"
+    );
+    assert_eq!(html::open_span(&style(9)), "<span class=\"yk-bright-red\">");
+}
@@ -0,0 +1,519 @@
+//! Snapshots of the human renderer with colors disabled.
+
+use std::sync::Arc;
+use yakou::diagnostic::{
+    color_mode::ColorMode, report::ReportType, report_builder::FileReportBuilder,
+    source_map::SourceMap, span::Span,
+};
+
+fn builder(source: &str) -> FileReportBuilder {
+    FileReportBuilder::new_source(source.to_string()).color_mode(ColorMode::Never)
+}
+
+fn render(builder: FileReportBuilder) -> String {
+    builder.render_to_string().unwrap()
+}
+
+#[test]
+fn out_of_range_spans_are_listed() {
+    let output = render(
+        builder("a\nb\nc\nd\ne\n")
+            .error(Span::single_line(2, 0, 1), "mixed".to_string())
+            .label(Span::multiple_line(4, 0, 2, 0), "reversed".to_string())
+            .build()
+            .label(Span::single_line(2, 0, 1), "fine".to_string())
+            .build()
+            .label(Span::single_line(99, 0, 1), "far away".to_string())
+            .build()
+            .label(Span::single_line(1, 10, 20), "past the end".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] mixed
+    ╭─[Unknown:2:0]
+  1 │    a
+  2 │    b
+    ·    ┬
+    ·    ╰── fine
+    ·    
+  3 │    c
+    · past the end (span out of range: 1:10-1:20)
+    · reversed (reversed span: 4:0-2:0)
+    · far away (span out of range: 99:0-99:1)
+────╯
+"
+    );
+}
+
+#[test]
+fn out_of_range_report_keeps_its_labels() {
+    let output = render(
+        builder("x\n")
+            .error(Span::single_line(99, 0, 3), "line 99".to_string())
+            .label(Span::single_line(99, 0, 3), "here".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] line 99
+    ╭─[Unknown:99:0]
+    · (span out of range: 99:0-99:3)
+    · here (span out of range: 99:0-99:3)
+────╯
+"
+    );
+}
+
+fn numbered_lines(count: usize) -> String {
+    (1..=count).map(|line| format!("line {}\n", line)).collect()
+}
+
+#[test]
+fn own_span_is_shown_and_long_gaps_are_folded() {
+    let output = render(
+        builder(&numbered_lines(45))
+            .error(Span::single_line(3, 0, 4), "own span".to_string())
+            .label(Span::single_line(40, 0, 4), "far label".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] own span
+   ╭─[Unknown:3:0]
+ 2 │    line 2
+ 3 │    line 3
+ 4 │    line 4
+   ⋮    lines omitted: 34
+39 │    line 39
+40 │    line 40
+   ·    ──┬─
+   ·      ╰───── far label
+   ·    
+41 │    line 41
+───╯
+"
+    );
+}
+
+#[test]
+fn short_gaps_are_not_folded() {
+    let output = render(
+        builder(&numbered_lines(20))
+            .error(Span::single_line(10, 0, 4), "gap".to_string())
+            .label(Span::single_line(10, 0, 4), "a".to_string())
+            .build()
+            .label(Span::single_line(14, 0, 4), "b".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] gap
+   ╭─[Unknown:10:0]
+ 9 │    line 9
+10 │    line 10
+   ·    ──┬─
+   ·      ╰───── a
+   ·    
+11 │    line 11
+12 │    line 12
+13 │    line 13
+14 │    line 14
+   ·    ──┬─
+   ·      ╰───── b
+   ·    
+15 │    line 15
+───╯
+"
+    );
+}
+
+#[test]
+fn overlapping_nested_and_empty_labels_are_stacked() {
+    let output = render(
+        builder("let value = compute(a, b);\n")
+            .error(Span::single_line(1, 4, 25), "overlaps".to_string())
+            .label(Span::single_line(1, 12, 25), "call".to_string())
+            .build()
+            .label(Span::single_line(1, 20, 21), "nested".to_string())
+            .build()
+            .label(Span::single_line(1, 4, 15), "overlapping".to_string())
+            .build()
+            .label(Span::single_line(1, 11, 11), "empty".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] overlaps
+  ╭─[Unknown:1:4]
+1 │    let value = compute(a, b);
+  ·        ─────┬─┬──────┬─┬────
+  ·             │ │      │ ╰────── nested
+  ·             │ │      │
+  ·             │ │      ╰──────── call
+  ·             │ │
+  ·             │ ╰─────────────── empty
+  ·             │
+  ·             ╰───────────────── overlapping
+  ·    
+──╯
+"
+    );
+}
+
+#[test]
+fn adjacent_labels_get_their_own_anchors() {
+    let output = render(
+        builder("a + b\n")
+            .error(Span::single_line(1, 0, 5), "adjacent".to_string())
+            .label(Span::single_line(1, 0, 1), "left".to_string())
+            .build()
+            .label(Span::single_line(1, 1, 4), "op".to_string())
+            .build()
+            .label(Span::single_line(1, 4, 5), "right".to_string())
+            .build()
+            .label(Span::single_line(1, 5, 5), "end".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] adjacent
+  ╭─[Unknown:1:0]
+1 │    a + b
+  ·    ┬─┬─┬┬
+  ·    │ │ │╰─ end
+  ·    │ │ │
+  ·    │ │ ╰── right
+  ·    │ │
+  ·    │ ╰──── op
+  ·    │
+  ·    ╰────── left
+  ·    
+──╯
+"
+    );
+}
+
+#[test]
+fn multi_line_labels_sharing_start_and_end_lines() {
+    let source = "fn main() {\n    if x {\n        y();\n    }\n}\n";
+    let output = render(
+        builder(source)
+            .error(Span::multiple_line(1, 10, 5, 1), "multi".to_string())
+            .label(Span::multiple_line(1, 10, 5, 1), "body".to_string())
+            .build()
+            .label(Span::multiple_line(2, 9, 4, 5), "if block".to_string())
+            .build()
+            .label(Span::multiple_line(2, 4, 4, 5), "if expr".to_string())
+            .build()
+            .label(Span::multiple_line(3, 8, 5, 0), "interleaved".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] multi
+  ╭─[Unknown:1:10]
+1 │ ╭────────▶ fn main() {
+2 │ │ ╭─┬────▶     if x {
+3 │ │ │ │ ╭──▶         y();
+4 │ │ ├─┼─┼──▶     }
+  · │ │ │ │    
+  · │ │ ╰─┼────────── if block
+  · │ │   │    
+  · │ ╰───┼────────── if expr
+5 │ ├─────┼──▶ }
+  · │     │    
+  · │     ╰────── interleaved
+  · │          
+  · ╰──────────── body
+──╯
+"
+    );
+}
+
+#[test]
+fn tabs_and_wide_characters_are_windowed() {
+    let source = format!("\tlet 名前 = \"{}\";\n", "漢字".repeat(30));
+    let output = render(
+        builder(&source)
+            .max_width(30)
+            .error(Span::single_line(1, 5, 7), "start".to_string())
+            .label(Span::single_line(1, 5, 7), "name".to_string())
+            .build()
+            .build()
+            .error(Span::single_line(1, 40, 42), "middle".to_string())
+            .label(Span::single_line(1, 40, 42), "wide".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] start
+  ╭─[Unknown:1:5]
+1 │        let 名前 = \"漢字漢字漢字…
+  ·            ──┬─
+  ·              ╰─────────────────── name
+  ·    
+──╯
+[error] middle
+  ╭─[Unknown:1:40]
+1 │    …字漢字漢字漢字漢字漢字漢字漢…
+  ·                 ──┬─
+  ·                   ╰─────────────── wide
+  ·    
+──╯
+"
+    );
+}
+
+#[test]
+fn labels_too_far_apart_get_their_own_windows() {
+    let source = format!(
+        "{}x{}y{}\nend\n",
+        "a".repeat(10),
+        "b".repeat(100),
+        "c".repeat(10)
+    );
+    let output = render(
+        builder(&source)
+            .max_width(30)
+            .error(Span::single_line(1, 10, 11), "far apart".to_string())
+            .label(Span::multiple_line(1, 2, 2, 3), "multi".to_string())
+            .build()
+            .label(Span::single_line(1, 10, 11), "x".to_string())
+            .build()
+            .label(Span::single_line(1, 111, 112), "y".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] far apart
+  ╭─[Unknown:1:10]
+1 │ ╭──▶ aaaaaaaaaaxbbbbbbbbbbbbbbbbbb…
+  · │              ┬
+  · │              ╰──────────────────── x
+  · │    
+1 │ │    …bbbbbbbbbbbbbbbbbbycccccccccc
+  · │                       ┬
+  · │                       ╰─────────── y
+  · │    
+2 │ ├──▶ end
+  · │    
+  · ╰──────── multi
+──╯
+"
+    );
+}
+
+#[test]
+fn child_points_into_another_file() {
+    let source_map = Arc::new(SourceMap::new());
+    let main = source_map.add_source(
+        "main.yk".to_string(),
+        "class A {}\nclass A {}\n".to_string(),
+    );
+    let other = source_map.add_source(
+        "other.yk".to_string(),
+        "// other\n\nclass A {}\n".to_string(),
+    );
+    let output = render(
+        FileReportBuilder::new(source_map, main)
+            .color_mode(ColorMode::Never)
+            .error(
+                Span::single_line(2, 6, 7),
+                "class `A` is defined twice".to_string(),
+            )
+            .label(Span::single_line(2, 6, 7), "redefined here".to_string())
+            .build()
+            .child(
+                ReportType::Note,
+                Span::single_line(3, 6, 7),
+                "first defined here".to_string(),
+            )
+            .file(other)
+            .label(Span::single_line(3, 6, 7), "first definition".to_string())
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] class `A` is defined twice
+  ╭─[main.yk:2:6]
+1 │    class A {}
+2 │    class A {}
+  ·          ┬
+  ·          ╰──── redefined here
+  ·    
+  ├─ [note] first defined here
+  ├─[other.yk:3:6]
+2 │    
+3 │    class A {}
+  ·          ┬
+  ·          ╰──── first definition
+  ·    
+──╯
+"
+    );
+}
+
+#[test]
+fn labels_in_other_files_get_their_own_sections() {
+    let source_map = Arc::new(SourceMap::new());
+    let main = source_map.add_source(
+        "main.yk".to_string(),
+        "import other\n\nlet a = other.value\n".to_string(),
+    );
+    let other = source_map.add_source(
+        "other.yk".to_string(),
+        "// 1\n// 2\n// 3\nprivate let value = 1\n// 5\n// 6\n".to_string(),
+    );
+    let output = render(
+        FileReportBuilder::new(source_map, main)
+            .color_mode(ColorMode::Never)
+            .error(
+                Span::single_line(3, 14, 19),
+                "`value` is private".to_string(),
+            )
+            .label(Span::single_line(3, 14, 19), "used here".to_string())
+            .build()
+            .label(
+                Span::single_line(4, 0, 7),
+                "declared private here".to_string(),
+            )
+            .file(other)
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] `value` is private
+  ╭─[main.yk:3:14]
+2 │    
+3 │    let a = other.value
+  ·                  ──┬──
+  ·                    ╰──── used here
+  ·    
+  ├─[other.yk:4:0]
+3 │    // 3
+4 │    private let value = 1
+  ·    ───┬───
+  ·       ╰────────────────── declared private here
+  ·    
+5 │    // 5
+──╯
+"
+    );
+}
+
+#[test]
+fn own_file_without_labels_keeps_its_section() {
+    let source_map = Arc::new(SourceMap::new());
+    let main = source_map.add_source("main.yk".to_string(), "let a = other.value\n".to_string());
+    let other = source_map.add_source(
+        "other.yk".to_string(),
+        "private let value = 1\n".to_string(),
+    );
+    let output = render(
+        FileReportBuilder::new(source_map, main)
+            .color_mode(ColorMode::Never)
+            .error(
+                Span::single_line(1, 14, 19),
+                "`value` is private".to_string(),
+            )
+            .label(
+                Span::single_line(1, 0, 7),
+                "declared private here".to_string(),
+            )
+            .file(other)
+            .build()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] `value` is private
+  ╭─[main.yk:1:14]
+1 │    let a = other.value
+  ├─[other.yk:1:0]
+1 │    private let value = 1
+  ·    ───┬───
+  ·       ╰────────────────── declared private here
+  ·    
+──╯
+"
+    );
+}
+
+#[test]
+fn multi_line_footers_are_indented() {
+    let output = render(
+        builder("open class A\nclass B : A\n")
+            .error(Span::single_line(2, 10, 11), "`A` is final".to_string())
+            .label(Span::single_line(2, 10, 11), "inherited here".to_string())
+            .build()
+            .note("classes are final by default".to_string())
+            .help("add `open` to the declaration\nor remove the implementation".to_string())
+            .child(
+                ReportType::Note,
+                Span::single_line(1, 11, 12),
+                "declared here".to_string(),
+            )
+            .help("children indent\ntheir footers too".to_string())
+            .build_child()
+            .build(),
+    );
+
+    assert_eq!(
+        output,
+        "\
+[error] `A` is final
+  ╭─[Unknown:2:10]
+1 │    open class A
+2 │    class B : A
+  ·              ┬
+  ·              ╰── inherited here
+  ·    
+  = note: classes are final by default
+  = help: add `open` to the declaration
+          or remove the implementation
+  ├─ [note] declared here
+  ├─[Unknown:1:11]
+1 │    open class A
+2 │    class B : A
+  = help: children indent
+          their footers too
+──╯
+"
+    );
+}
@@ -0,0 +1,94 @@
+use serde_json::Value;
+use yakou::diagnostic::{
+    codes, emitter::ErrorFormat, report::ReportType, report_builder::FileReportBuilder, span::Span,
+    suggestion::Applicability,
+};
+
+const SOURCE: &str = "class Yakou\n\nimpl Yakou {\n    fun main() {}\n}\n";
+
+fn render_sarif() -> Value {
+    let output = FileReportBuilder::new_source(SOURCE.to_string())
+        .error_format(ErrorFormat::Sarif)
+        .error(
+            Span::single_line(3, 5, 10),
+            "class `Yakou` is final".to_string(),
+        )
+        .code(&codes::E03)
+        .label(Span::single_line(3, 0, 4), "implemented here".to_string())
+        .hint("mark the class as open".to_string())
+        .suggestion(
+            Span::single_line(1, 0, 0),
+            "open ".to_string(),
+            Applicability::MachineApplicable,
+        )
+        .build()
+        .label(Span::multiple_line(3, 0, 5, 1), "in this block".to_string())
+        .build()
+        .note("classes are final by default".to_string())
+        .child(
+            ReportType::Note,
+            Span::single_line(1, 0, 11),
+            "declared here".to_string(),
+        )
+        .label(Span::single_line(1, 6, 11), "`Yakou`".to_string())
+        .build()
+        .build()
+        .warning(Span::single_line(4, 4, 7), "unused function".to_string())
+        .build()
+        .render_to_string()
+        .unwrap();
+
+    serde_json::from_str(&output).unwrap()
+}
+
+#[test]
+fn sarif_log_validates_against_schema() {
+    let schema: Value = serde_json::from_str(include_str!("fixtures/sarif-2.1.0.json")).unwrap();
+    let validator = jsonschema::draft7::new(&schema).unwrap();
+    let log = render_sarif();
+
+    let errors: Vec<String> = validator
+        .iter_errors(&log)
+        .map(|error| format!("{} at {}", error, error.instance_path()))
+        .collect();
+    assert!(errors.is_empty(), "{:#?}", errors);
+}
+
+#[test]
+fn sarif_log_keeps_labels_suggestions_and_children() {
+    let log = render_sarif();
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+
+    let result = &results[0];
+    assert_eq!(result["ruleId"], "E03");
+    assert_eq!(result["level"], "error");
+    assert_eq!(
+        result["message"]["text"],
+        "class `Yakou` is final\nnote: classes are final by default"
+    );
+
+    let related: Vec<&str> = result["relatedLocations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|location| location["message"]["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        related,
+        [
+            "implemented here (hint: mark the class as open)",
+            "in this block",
+            "note: declared here",
+            "`Yakou`",
+        ]
+    );
+
+    let replacement = &result["fixes"][0]["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["insertedContent"]["text"], "open ");
+    assert_eq!(replacement["deletedRegion"]["startLine"], 1);
+    assert_eq!(replacement["deletedRegion"]["startColumn"], 1);
+    assert!(replacement["deletedRegion"].get("byteOffset").is_none());
+
+    assert_eq!(results[1]["level"], "warning");
+}
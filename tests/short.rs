@@ -0,0 +1,53 @@
+//! Output of the one-line-per-message formats.
+
+use yakou::diagnostic::{
+    color_mode::ColorMode, report::ReportType, report_builder::FileReportBuilder, span::Span,
+    theme::HIGH_CONTRAST,
+};
+
+fn builder() -> FileReportBuilder {
+    FileReportBuilder::new_source("let a = b;\nlet c = d;\n".to_string())
+        .error(Span::single_line(1, 4, 5), "mismatched".to_string())
+        .label(Span::single_line(2, 4, 5), "second".to_string())
+        .build()
+        .label(Span::single_line(1, 8, 9), "first".to_string())
+        .build()
+        .build()
+}
+
+fn print_short(mut builder: FileReportBuilder) -> String {
+    let mut output = Vec::new();
+    builder.print_short(&mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn labels_are_sorted_by_line_then_column() {
+    let output = print_short(builder().color_mode(ColorMode::Never));
+
+    assert_eq!(
+        output,
+        "\
+Unknown:1:5: error: mismatched
+Unknown:1:9: note: first
+Unknown:2:5: note: second
+"
+    );
+}
+
+#[test]
+fn short_output_honours_color_mode_and_theme() {
+    let output = print_short(builder().color_mode(ColorMode::Always));
+    let error = HIGH_CONTRAST.header_style(ReportType::Error);
+    assert!(!output.contains(&error.to_string()));
+
+    let output = print_short(
+        builder()
+            .theme(HIGH_CONTRAST.clone())
+            .color_mode(ColorMode::Always),
+    );
+    assert!(output.starts_with(&format!(
+        "Unknown:1:5: {}error{:#}: mismatched",
+        error, error
+    )));
+}
@@ -0,0 +1,93 @@
+//! Counting, limits and the closing summary of the diagnostic sink.
+
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+use yakou::diagnostic::{
+    color_mode::ColorMode,
+    report::{Report, ReportType},
+    short::ShortEmitter,
+    sink::DiagnosticSink,
+    source_map::SourceMap,
+    span::Span,
+    theme::HIGH_CONTRAST,
+};
+
+/// A writer the test can read back after handing it to the sink.
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Buffer {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+#[test]
+fn reports_after_the_error_limit_are_noted() {
+    let source_map = Arc::new(SourceMap::new());
+    let file_id = source_map.add_source("a.yk".to_string(), "a\nb\nc\nd\n".to_string());
+    let buffer = Buffer::default();
+    let sink = DiagnosticSink::new(
+        source_map,
+        Arc::new(ShortEmitter::default()),
+        Box::new(buffer.clone()),
+    )
+    .max_errors(2);
+
+    for line in 1..=4 {
+        sink.report(
+            file_id,
+            Report::error(Span::single_line(line, 0, 1), format!("error {}", line)),
+        );
+    }
+    sink.finish().unwrap();
+
+    assert_eq!(sink.error_count(), 2);
+    assert_eq!(sink.suppressed_count(), 2);
+    assert_eq!(
+        buffer.contents(),
+        "\
+a.yk:1:1: error: error 1
+a.yk:2:1: error: error 2
+note: 2 further reports were suppressed after reaching the error limit
+error: aborting due to 2 previous errors
+"
+    );
+}
+
+#[test]
+fn summary_is_styled_with_the_emitter_theme() {
+    let source_map = Arc::new(SourceMap::new());
+    let file_id = source_map.add_source("a.yk".to_string(), "a\n".to_string());
+    let buffer = Buffer::default();
+    let sink = DiagnosticSink::new(
+        source_map,
+        Arc::new(ShortEmitter::new(HIGH_CONTRAST.clone())),
+        Box::new(buffer.clone()),
+    )
+    .color_mode(ColorMode::Always);
+
+    sink.report(
+        file_id,
+        Report::error(Span::single_line(1, 0, 1), "oops".to_string()),
+    );
+    sink.finish().unwrap();
+
+    let style = HIGH_CONTRAST.header_style(ReportType::Error);
+    assert!(buffer.contents().ends_with(&format!(
+        "{}error{:#}: aborting due to 1 previous error\n",
+        style, style
+    )));
+}
@@ -0,0 +1,192 @@
+use std::{fs, path::PathBuf, sync::Arc};
+use yakou::diagnostic::{
+    color_mode::ColorMode,
+    human_emitter::HumanEmitter,
+    position::Position,
+    report::Report,
+    report_builder::FileReportBuilder,
+    sink::DiagnosticSink,
+    source::Source,
+    source_map::SourceMap,
+    span::Span,
+    suggestion::{Applicability, Suggestion},
+};
+
+fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("yakou-{}-{}", std::process::id(), name));
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn crlf_and_lone_cr_end_lines() {
+    let source = Source::new("\u{feff}ab\r\ncd\ref\n".to_string());
+
+    assert!(source.has_bom);
+    let lines: Vec<(usize, &str)> = source
+        .lines
+        .iter()
+        .map(|line| (line.offset, line.chars.as_str()))
+        .collect();
+    assert_eq!(lines, [(0, "ab"), (4, "cd"), (7, "ef")]);
+    assert_eq!(source.position(7).line, 3);
+}
+
+#[test]
+fn suggestion_diff_has_no_carriage_returns() {
+    let source = Source::new("ab\r\ncd\r\n".to_string());
+    let suggestion = Suggestion::new(
+        Span::multiple_line(1, 1, 2, 1).resolve(&source),
+        "X".to_string(),
+        Applicability::MachineApplicable,
+    );
+
+    assert_eq!(
+        suggestion.diff(&source),
+        Some(("ab\ncd".to_string(), "aXd".to_string()))
+    );
+}
+
+#[test]
+fn invalid_utf8_is_loaded_with_a_warning() {
+    let path = temp_file("lossy.yk", b"\xef\xbb\xbflet \xffx\n");
+    let source_map = SourceMap::new();
+    let file_id = source_map.add_path(&path);
+
+    let source = source_map.get(file_id).unwrap();
+    assert_eq!(source.lines[0].chars, "let \u{fffd}x");
+    let warnings = source_map.load_warnings(file_id);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].offset, 4);
+    assert!(warnings[0].message.contains("offset 7"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn sink_reports_load_problems_once() {
+    let lossy = temp_file("sink.yk", b"let \xffx\n");
+    let source_map = Arc::new(SourceMap::new());
+    let lossy_id = source_map.add_path(&lossy);
+    let missing_id = source_map.add_path(&std::env::temp_dir().join("yakou-missing.yk"));
+
+    let sink = DiagnosticSink::new(
+        source_map,
+        Arc::new(HumanEmitter::default()),
+        Box::new(std::io::sink()),
+    );
+    for _ in 0..2 {
+        sink.report(
+            lossy_id,
+            Report::error(Span::single_line(1, 0, 3), "a".to_string()),
+        );
+        sink.report(
+            missing_id,
+            Report::error(Span::single_line(1, 0, 3), "b".to_string()),
+        );
+    }
+
+    assert_eq!(sink.error_count(), 2);
+    assert_eq!(sink.warning_count(), 2);
+    fs::remove_file(lossy).unwrap();
+}
+
+#[test]
+fn positions_and_offsets_round_trip() {
+    for text in ["ab\ncd", "ab\ncd\n", "aé漢\r\nx\n", ""] {
+        let source = Source::new(text.to_string());
+        for line in &source.lines {
+            for column in 0..=line.chars.chars().count() {
+                let position = Position::new(line.line_number, column);
+                let offset = source.offset(position).unwrap();
+                assert_eq!(
+                    source.position(offset),
+                    position,
+                    "{:?} at {}",
+                    text,
+                    offset
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn offsets_inside_a_char_resolve_to_that_char() {
+    let source = Source::new("aé漢\nx\n".to_string());
+
+    assert_eq!(source.position(2), Position::new(1, 1));
+    assert_eq!(source.position(4), Position::new(1, 2));
+    assert_eq!(source.position(5), Position::new(1, 2));
+    assert_eq!(source.offset(Position::new(1, 2)), Some(3));
+}
+
+#[test]
+fn offsets_at_or_past_the_end_are_clamped() {
+    let without_newline = Source::new("ab\ncd".to_string());
+    assert_eq!(without_newline.position(5), Position::new(2, 2));
+    assert_eq!(without_newline.position(99), Position::new(2, 2));
+
+    let with_newline = Source::new("ab\ncd\n".to_string());
+    assert_eq!(with_newline.position(6), Position::new(2, 2));
+    assert_eq!(with_newline.position(99), Position::new(2, 2));
+
+    assert_eq!(with_newline.offset(Position::new(2, 3)), None);
+    assert_eq!(with_newline.offset(Position::new(3, 0)), None);
+    assert_eq!(with_newline.offset(Position::new(0, 0)), None);
+}
+
+#[test]
+fn byte_ranges_resolve_through_the_builder() {
+    let output = FileReportBuilder::new_source("let é = 1;\nlet 漢字 = é;\n".to_string())
+        .color_mode(ColorMode::Never)
+        .error(Span::byte_range(16, 22), "byte range".to_string())
+        .label(Span::byte_range(16, 22), "wide".to_string())
+        .build()
+        .label(Span::byte_range(4, 6), "accent".to_string())
+        .build()
+        .build()
+        .render_to_string()
+        .unwrap();
+
+    assert_eq!(
+        output,
+        "\
+[error] byte range
+  ╭─[Unknown:2:4]
+1 │    let é = 1;
+  ·        ┬
+  ·        ╰────── accent
+  ·    
+2 │    let 漢字 = é;
+  ·        ──┬─
+  ·          ╰─────── wide
+  ·    
+──╯
+"
+    );
+}
+
+#[test]
+fn load_warnings_are_promoted_by_warnings_as_errors() {
+    let lossy = temp_file("werror.yk", b"let \xffx\n");
+    let source_map = Arc::new(SourceMap::new());
+    let lossy_id = source_map.add_path(&lossy);
+
+    let sink = DiagnosticSink::new(
+        source_map,
+        Arc::new(HumanEmitter::default()),
+        Box::new(std::io::sink()),
+    )
+    .warnings_as_errors(true)
+    .max_errors(1);
+    sink.report(
+        lossy_id,
+        Report::error(Span::single_line(1, 0, 3), "a".to_string()),
+    );
+
+    assert_eq!(sink.error_count(), 1);
+    assert_eq!(sink.warning_count(), 0);
+    assert_eq!(sink.suppressed_count(), 1);
+    fs::remove_file(lossy).unwrap();
+}
@@ -0,0 +1,93 @@
+//! Built-in themes and themes loaded from TOML.
+
+use anstyle::{Ansi256Color, AnsiColor, Color, RgbColor, Style};
+use std::fs;
+use yakou::diagnostic::theme::{parse_style, Theme, HEAVY_BOX, PLAIN_ASCII};
+
+#[test]
+fn toml_overrides_its_base() {
+    let theme = Theme::from_toml(
+        r##"
+        base = "heavy"
+        label_palette = ["bright_red", "ansi256:208", "#56b4e9"]
+
+        [characters]
+        underline = "~"
+
+        [styles]
+        error = "bold underline red"
+        "##,
+    )
+    .unwrap();
+
+    assert_eq!(
+        theme.label_palette,
+        [
+            Color::Ansi(AnsiColor::BrightRed),
+            Color::Ansi256(Ansi256Color(208)),
+            Color::Rgb(RgbColor(0x56, 0xb4, 0xe9)),
+        ]
+    );
+    assert_eq!(theme.character_set.underline, '~');
+    assert_eq!(
+        theme.character_set.vertical_bar,
+        HEAVY_BOX.character_set.vertical_bar
+    );
+    assert_eq!(
+        theme.error,
+        Style::new()
+            .bold()
+            .underline()
+            .fg_color(Some(Color::Ansi(AnsiColor::Red)))
+    );
+    assert_eq!(theme.gutter, HEAVY_BOX.gutter);
+}
+
+#[test]
+fn builtin_themes_are_found_by_name() {
+    assert_eq!("ascii".parse::<Theme>().unwrap(), *PLAIN_ASCII);
+    assert_eq!(Theme::from_toml("").unwrap(), Theme::default());
+    assert!("compact".parse::<Theme>().is_err());
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    assert!(Theme::from_toml("colour = \"red\"").is_err());
+    assert!(Theme::from_toml("[styles]\nerrors = \"red\"").is_err());
+    assert!(Theme::from_toml("[characters]\nunderscore = \"_\"").is_err());
+}
+
+#[test]
+fn bad_colors_are_rejected() {
+    for text in [
+        "label_palette = [\"purple\"]",
+        "label_palette = [\"ansi256:300\"]",
+        "label_palette = [\"#12345\"]",
+        "[styles]\nerror = \"bold reddish\"",
+    ] {
+        assert!(Theme::from_toml(text).is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn a_style_has_at_most_one_color() {
+    assert_eq!(
+        parse_style("bold red"),
+        Ok(Style::new()
+            .bold()
+            .fg_color(Some(Color::Ansi(AnsiColor::Red))))
+    );
+    assert!(parse_style("bold red blue").is_err());
+}
+
+#[test]
+fn loading_a_file_names_it_in_errors() {
+    let path = std::env::temp_dir().join(format!("yakou-{}-theme.toml", std::process::id()));
+    fs::write(&path, "base = \"rounded\"\n[styles]\ngutter = \"dimmed\"\n").unwrap();
+    assert_eq!(Theme::load(&path).unwrap().gutter, Style::new().dimmed());
+
+    fs::write(&path, "base = \"nope\"\n").unwrap();
+    let error = Theme::load(&path).unwrap_err();
+    assert!(error.contains(&path.display().to_string()), "{}", error);
+    fs::remove_file(path).unwrap();
+}